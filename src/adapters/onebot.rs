@@ -1,6 +1,8 @@
 use crate::config::{AppConfig, BotConfig};
+use crate::dispatcher::{Dispatcher, Job, channel_key};
 use crate::event::{BotStatus, Context, Event, EventType, LoginUser, SendPacket};
 use crate::matcher::Matcher;
+use crate::metrics;
 use crate::scheduler::Scheduler;
 use crate::{error, info, plugins, warn};
 use futures_util::future::BoxFuture;
@@ -88,6 +90,7 @@ pub async fn run_bot_loop(
             }
         }
         tokio::time::sleep(Duration::from_secs(3)).await;
+        metrics::inc_counter(metrics::RECONNECTS, &[("bot", bot_url.as_str())]);
     }
 }
 
@@ -122,6 +125,13 @@ async fn connect_and_listen(
 
     let writer: LockedWriter = Arc::new(AsyncMutex::new(Box::new(write_half)));
     let matcher = Arc::new(Matcher::new());
+    let dispatcher = {
+        let guard = global_config.read().unwrap();
+        Dispatcher::global(
+            guard.pipeline.max_concurrency,
+            guard.pipeline.channel_queue_size,
+        )
+    };
 
     // 初始化 Bot 状态容器
     let bot_status = Arc::new(RwLock::new(BotStatus {
@@ -150,7 +160,7 @@ async fn connect_and_listen(
 
             // 构建临时上下文用于调用 API
             let ctx = Context {
-                event: EventType::Init,
+                event: Arc::new(EventType::Init),
                 config: config_ref,
                 config_save_lock: save_lock_ref,
                 db: db_ref,
@@ -194,10 +204,26 @@ async fn connect_and_listen(
         });
     }
 
+    let mut result: Result<(), BotError> = Ok(());
     while let Some(message) = read_half.next().await {
         match message {
             Ok(WsMessage::Text(text)) => {
                 let mut data = text.as_bytes().to_vec();
+                let event: Event = match simd_json::to_owned_value(&mut data) {
+                    Ok(v) => v,
+                    Err(_) => continue,
+                };
+
+                // 交互等待者与 API 响应在读循环中直接分发，不进入频道队列，
+                // 否则正在等待输入/响应的处理器会与排在其后的事件互相阻塞
+                let event = match matcher.dispatch(event).await {
+                    Some(e) => e,
+                    None => continue,
+                };
+
+                // 获取当前的 bot 状态快照
+                let current_status = bot_status.read().unwrap().clone();
+                let key = channel_key(&current_status.login_user.id, &event);
 
                 let writer = writer.clone();
                 let config = global_config.clone();
@@ -206,14 +232,10 @@ async fn connect_and_listen(
                 let save_lock = save_lock.clone();
                 let config_path = config_path.clone();
                 let matcher = matcher.clone();
-                let bot_status_ref = bot_status.clone();
-
-                tokio::spawn(async move {
-                    // 获取当前的 bot 状态快照
-                    let current_status = bot_status_ref.read().unwrap().clone();
 
-                    if let Err(e) = process_frame(
-                        &mut data,
+                let job: Job = Box::pin(async move {
+                    if let Err(e) = process_event(
+                        event,
                         writer,
                         config,
                         db,
@@ -228,13 +250,37 @@ async fn connect_and_listen(
                         error!(target: "Bot", "Event processing error: {}", e);
                     }
                 });
+
+                if !dispatcher.dispatch(key.clone(), job) {
+                    warn!(target: "Bot", "频道 [{}] 事件积压，已丢弃新事件", key);
+                }
+            }
+            Ok(WsMessage::Close(_)) => break,
+            Err(e) => {
+                result = Err(Box::new(e));
+                break;
             }
-            Ok(WsMessage::Close(_)) => return Ok(()),
-            Err(e) => return Err(Box::new(e)),
             _ => {}
         }
     }
-    Ok(())
+
+    // 通知插件连接已断开 (未获取到登录信息时 on_connected 也未触发过，无需通知)
+    let status = bot_status.read().unwrap().clone();
+    if status.login_user.id != "0" {
+        let ctx = Context {
+            event: Arc::new(EventType::Init),
+            config: global_config,
+            config_save_lock: save_lock,
+            db,
+            scheduler,
+            matcher,
+            config_path,
+            bot: status,
+        };
+        plugins::do_disconnected(ctx).await;
+    }
+
+    result
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -260,6 +306,47 @@ pub async fn process_frame(
         None => return Ok(()),
     };
 
+    process_event(
+        event,
+        writer,
+        config,
+        db,
+        scheduler,
+        save_lock,
+        config_path,
+        matcher,
+        bot,
+    )
+    .await
+}
+
+/// 处理已经过等待者分发的事件：黑白名单过滤后送入插件流水线
+#[allow(clippy::too_many_arguments)]
+pub async fn process_event(
+    event: Event,
+    writer: LockedWriter,
+    config: Arc<RwLock<AppConfig>>,
+    db: DatabaseConnection,
+    scheduler: Arc<Scheduler>,
+    save_lock: Arc<AsyncMutex<()>>,
+    config_path: String,
+    matcher: Arc<Matcher>,
+    bot: BotStatus,
+) -> Result<(), BotError> {
+    metrics::inc_counter(
+        metrics::EVENTS_RECEIVED,
+        &[
+            (
+                "post_type",
+                event
+                    .get("post_type")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown"),
+            ),
+            ("bot", bot.login_user.id.as_str()),
+        ],
+    );
+
     // 全局黑白名单过滤
     // 仅过滤带有 group_id 的事件（即群相关事件）
     let group_id = event
@@ -287,7 +374,7 @@ pub async fn process_frame(
     }
 
     let ctx = Context {
-        event: EventType::Onebot(event),
+        event: Arc::new(EventType::Onebot(event)),
         config,
         config_save_lock: save_lock,
         db,
@@ -332,7 +419,7 @@ where
         simd_json::to_owned_value(&mut json_bytes).map_err(|e| Box::new(e) as BotError)?;
 
     // 捕获原始事件以便在 BeforeSend 中传递
-    let original_event = match &ctx.event {
+    let original_event = match &*ctx.event {
         EventType::Onebot(ev) => Some(ev.clone()),
         EventType::BeforeSend(pkt) => pkt.original_event.clone(),
         EventType::Init => None,
@@ -345,7 +432,7 @@ where
     };
 
     let new_ctx = Context {
-        event: EventType::BeforeSend(packet),
+        event: Arc::new(EventType::BeforeSend(packet)),
         config: ctx.config.clone(),
         config_save_lock: ctx.config_save_lock.clone(),
         db: ctx.db.clone(),
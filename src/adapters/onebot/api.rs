@@ -4,11 +4,12 @@
 use super::{LockedWriter, send_frame_raw};
 use crate::event::Context;
 use crate::message::Message;
+use crate::metrics;
 use serde::{Deserialize, Serialize};
 use simd_json::OwnedValue;
 use simd_json::derived::{ValueObjectAccess, ValueObjectAccessAsScalar};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub type ApiError = Box<dyn std::error::Error + Send + Sync>;
 
@@ -51,10 +52,22 @@ where
     let wait_future = ctx.matcher.wait_resp(echo, Duration::from_secs(60));
 
     // 发送请求
+    let started = Instant::now();
     send_frame_raw(writer, json_str).await?;
 
     // 等待响应
-    let resp_event = wait_future.await.ok_or("API 请求超时")?;
+    let resp_event = match wait_future.await {
+        Some(ev) => ev,
+        None => {
+            metrics::inc_counter(metrics::API_TIMEOUTS, &[("action", action)]);
+            return Err("API 请求超时".into());
+        }
+    };
+    metrics::observe(
+        metrics::API_LATENCY,
+        &[("action", action)],
+        started.elapsed().as_secs_f64(),
+    );
 
     // 解析响应
     // 响应格式: { status, retcode, data, echo }
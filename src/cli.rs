@@ -0,0 +1,362 @@
+use crate::config::{AppConfig, data_dir};
+use crate::db::{self, dialect};
+use crate::plugins::recorder::{entity as records, interactions, raw};
+use crate::plugins::{self, ciyi, ping_pong, shindan};
+use chrono::{Local, NaiveDate, TimeZone};
+use clap::{Parser, Subcommand};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection,
+    EntityTrait, IntoActiveModel, Iterable, PaginatorTrait, PrimaryKeyToColumn, QueryFilter,
+    QueryOrder, QuerySelect, Statement,
+};
+use std::path::PathBuf;
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
+
+type CliError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Parser)]
+#[command(name = "ayjx", version, about = "ayjx 机器人框架")]
+pub struct Cli {
+    /// 配置文件路径
+    #[arg(short, long, default_value = "config.toml")]
+    pub config: String,
+
+    /// 数据目录 (数据库、插件数据、日志)
+    #[arg(short, long, default_value = "data")]
+    pub data_dir: PathBuf,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// 校验配置文件 (解析、密钥引用、插件字段类型)，有错误时以非零状态退出
+    CheckConfig,
+    /// 执行插件配置迁移与数据库迁移
+    Migrate,
+    /// 导出聊天记录为 JSON Lines
+    Export {
+        /// 仅导出指定群
+        #[arg(long)]
+        group: Option<i64>,
+        /// 起始日期 (YYYY-MM-DD，含)
+        #[arg(long)]
+        since: Option<NaiveDate>,
+        /// 结束日期 (YYYY-MM-DD，含)
+        #[arg(long)]
+        until: Option<NaiveDate>,
+        /// 输出文件，缺省时输出到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// 整理数据库文件，回收已删除数据占用的空间
+    Vacuum,
+    /// 列出已注册的插件及其状态
+    ListPlugins,
+    /// 将现有 SQLite 数据库的数据复制到配置中的数据库 (如 PostgreSQL / MySQL)
+    CopyDb {
+        /// 源 SQLite 数据库文件路径或 sqlite: 地址
+        #[arg(long)]
+        from: String,
+    },
+}
+
+/// 执行维护子命令
+pub async fn run(command: Command, config_path: &str) -> Result<(), CliError> {
+    match command {
+        Command::CheckConfig => check_config(config_path).await,
+        Command::Migrate => migrate(config_path).await,
+        Command::Export {
+            group,
+            since,
+            until,
+            output,
+        } => export(config_path, group, since, until, output).await,
+        Command::Vacuum => vacuum(config_path).await,
+        Command::ListPlugins => list_plugins(config_path).await,
+        Command::CopyDb { from } => copy_db(config_path, &from).await,
+    }
+}
+
+/// 按配置文件中的 [database] 连接数据库
+async fn open_db(config_path: &str) -> Result<(AppConfig, DatabaseConnection), CliError> {
+    let cfg = AppConfig::load(config_path).await?;
+    let db = db::init(&cfg.database).await?;
+    Ok((cfg, db))
+}
+
+async fn check_config(config_path: &str) -> Result<(), CliError> {
+    let mut cfg = AppConfig::load(config_path).await?;
+    plugins::normalize_config(&mut cfg);
+
+    let errors = plugins::validate_config(&cfg);
+    if errors.is_empty() {
+        println!("✅ 配置文件 {} 校验通过", config_path);
+        return Ok(());
+    }
+
+    for e in &errors {
+        eprintln!("❌ {}", e);
+    }
+    Err(format!("共发现 {} 处配置错误", errors.len()).into())
+}
+
+async fn migrate(config_path: &str) -> Result<(), CliError> {
+    let mut cfg = AppConfig::load(config_path).await?;
+    if plugins::normalize_config(&mut cfg) {
+        cfg.save(config_path).await?;
+        println!("✅ 配置文件已更新: {}", config_path);
+    } else {
+        println!("配置文件已是最新版本，无需迁移");
+    }
+
+    let db = db::init(&cfg.database).await?;
+    let applied = plugins::run_db_migrations(&db).await?;
+    let _ = db.close().await;
+    println!("✅ 数据库迁移完成，本次应用 {} 项", applied);
+    Ok(())
+}
+
+fn day_start(date: NaiveDate) -> i64 {
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .map(|t| t.timestamp())
+        .unwrap_or(0)
+}
+
+/// 导出时每页读取的记录数
+const EXPORT_BATCH: u64 = 1000;
+
+async fn export(
+    config_path: &str,
+    group: Option<i64>,
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    output: Option<PathBuf>,
+) -> Result<(), CliError> {
+    let (_, db) = open_db(config_path).await?;
+
+    let mut query = records::Entity::find();
+    if let Some(gid) = group {
+        query = query.filter(records::Column::GroupId.eq(gid));
+    }
+    if let Some(d) = since {
+        query = query.filter(records::Column::Time.gte(day_start(d)));
+    }
+    if let Some(d) = until {
+        query = query.filter(records::Column::Time.lt(day_start(d + chrono::Duration::days(1))));
+    }
+
+    let mut writer: BufWriter<Box<dyn AsyncWrite + Unpin + Send>> = match &output {
+        Some(path) => BufWriter::new(Box::new(tokio::fs::File::create(path).await?)),
+        None => BufWriter::new(Box::new(tokio::io::stdout())),
+    };
+
+    // 按 id 游标分页逐页写出，避免一次性将全部记录读入内存
+    let mut last_id = 0;
+    let mut count = 0;
+    loop {
+        let rows = query
+            .clone()
+            .filter(records::Column::Id.gt(last_id))
+            .order_by_asc(records::Column::Id)
+            .limit(EXPORT_BATCH)
+            .into_json()
+            .all(&db)
+            .await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        last_id = last.get("id").and_then(|v| v.as_i64()).unwrap_or(i64::MAX);
+
+        for row in &rows {
+            writer.write_all(row.to_string().as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+        }
+        count += rows.len();
+    }
+    writer.flush().await?;
+
+    match output {
+        Some(path) => eprintln!("✅ 已导出 {} 条记录到 {}", count, path.display()),
+        None => eprintln!("✅ 已导出 {} 条记录", count),
+    }
+
+    let _ = db.close().await;
+    Ok(())
+}
+
+async fn vacuum(config_path: &str) -> Result<(), CliError> {
+    let db_path = data_dir().join("bot.db");
+    let size = |p: &PathBuf| std::fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+    let before = size(&db_path);
+
+    let (cfg, db) = open_db(config_path).await?;
+    let backend = db.get_database_backend();
+    if backend == DatabaseBackend::Sqlite {
+        db.execute(Statement::from_string(
+            backend,
+            "PRAGMA wal_checkpoint(TRUNCATE);".to_owned(),
+        ))
+        .await?;
+    }
+    let done = dialect::vacuum(&db).await?;
+    let _ = db.close().await;
+
+    if !done {
+        println!("当前数据库 ({:?}) 无需整理", backend);
+    } else if cfg.database.url.is_empty() {
+        let after = size(&db_path);
+        println!(
+            "✅ 数据库整理完成: {:.2} MB -> {:.2} MB",
+            before as f64 / 1024.0 / 1024.0,
+            after as f64 / 1024.0 / 1024.0
+        );
+    } else {
+        println!("✅ 数据库整理完成");
+    }
+    Ok(())
+}
+
+async fn list_plugins(config_path: &str) -> Result<(), CliError> {
+    let mut cfg = AppConfig::load(config_path).await?;
+    plugins::normalize_config(&mut cfg);
+
+    println!("{:<20} {:<6} {:<8} 钩子", "插件", "启用", "配置版本");
+    for p in plugins::get_plugins() {
+        let enabled = cfg
+            .plugins
+            .get(p.name)
+            .and_then(|v| v.get("enabled"))
+            .and_then(|x| x.as_bool())
+            .unwrap_or(false);
+
+        let mut hooks = Vec::new();
+        if p.on_init.is_some() {
+            hooks.push("init");
+        }
+        if p.on_connected.is_some() {
+            hooks.push("connected");
+        }
+        if p.on_disconnected.is_some() {
+            hooks.push("disconnected");
+        }
+        if p.on_config_changed.is_some() {
+            hooks.push("config_changed");
+        }
+
+        println!(
+            "{:<20} {:<6} v{:<7} {}",
+            p.name,
+            if enabled { "是" } else { "否" },
+            p.config_version(),
+            hooks.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// 每批复制的行数
+const COPY_BATCH: u64 = 1000;
+
+async fn copy_db(config_path: &str, from: &str) -> Result<(), CliError> {
+    let src_url = if from.starts_with("sqlite:") {
+        from.to_string()
+    } else {
+        format!("sqlite:{}", from)
+    };
+
+    let (_, dst) = open_db(config_path).await?;
+    let src = db::connect(&src_url, 1, 1).await?;
+    if src.get_database_backend() != DatabaseBackend::Sqlite {
+        return Err("源数据库必须是 SQLite".into());
+    }
+
+    // 先在目标库上建立完整的表结构
+    plugins::run_db_migrations(&dst).await?;
+
+    let mut total = 0;
+    total += copy_entity(&src, &dst, records::Entity).await?;
+    total += copy_entity(&src, &dst, interactions::entity::Entity).await?;
+    total += copy_entity(&src, &dst, raw::entity::Entity).await?;
+    total += copy_entity(&src, &dst, ping_pong::entity::Entity).await?;
+    total += copy_entity(&src, &dst, ciyi::entity::state::Entity).await?;
+    total += copy_entity(&src, &dst, ciyi::entity::record::Entity).await?;
+    total += copy_entity(&src, &dst, shindan::entity::user_stats::Entity).await?;
+    total += copy_entity(&src, &dst, shindan::entity::item_stats::Entity).await?;
+    total += copy_entity(&src, &dst, db::store::entity::Entity).await?;
+
+    // 显式写入了自增 id，PostgreSQL 需同步序列，避免后续插入主键冲突
+    if dst.get_database_backend() == DatabaseBackend::Postgres {
+        for table in [
+            "message_records",
+            "message_interactions",
+            "message_raw",
+            "plugin_ping_stats",
+            "ciyi_win_record",
+        ] {
+            dst.execute_unprepared(&format!(
+                "SELECT setval(pg_get_serial_sequence('{0}', 'id'), COALESCE(MAX(id), 0) + 1, false) FROM {0}",
+                table
+            ))
+            .await?;
+        }
+    }
+
+    let _ = src.close().await;
+    let _ = dst.close().await;
+    println!("✅ 数据复制完成，共 {} 行", total);
+    Ok(())
+}
+
+/// 按主键顺序分批复制一张表，源库中不存在的表与目标库中已有数据的表会被跳过
+async fn copy_entity<E>(
+    src: &DatabaseConnection,
+    dst: &DatabaseConnection,
+    entity: E,
+) -> Result<u64, CliError>
+where
+    E: EntityTrait,
+    E::Model: IntoActiveModel<E::ActiveModel> + Sync,
+    E::ActiveModel: ActiveModelTrait<Entity = E> + Send,
+{
+    let table = entity.table_name().to_string();
+
+    let exists = src
+        .query_one(Statement::from_sql_and_values(
+            DatabaseBackend::Sqlite,
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [table.clone().into()],
+        ))
+        .await?
+        .is_some();
+    if !exists {
+        println!("- {}: 源库中不存在，跳过", table);
+        return Ok(0);
+    }
+
+    if E::find().count(dst).await? > 0 {
+        println!("- {}: 目标表已有数据，跳过", table);
+        return Ok(0);
+    }
+
+    let mut query = E::find();
+    for pk in E::PrimaryKey::iter() {
+        query = query.order_by_asc(pk.into_column());
+    }
+
+    let mut pages = query.paginate(src, COPY_BATCH);
+    let mut copied = 0;
+    while let Some(rows) = pages.fetch_and_next().await? {
+        let len = rows.len() as u64;
+        E::insert_many(rows.into_iter().map(|m| m.into_active_model()))
+            .exec_without_returning(dst)
+            .await?;
+        copied += len;
+    }
+
+    println!("- {}: 已复制 {} 行", table, copied);
+    Ok(copied)
+}
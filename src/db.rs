@@ -1,28 +1,68 @@
 #![allow(dead_code)]
 
-use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbErr, Statement};
-use std::path::Path;
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseBackend, DatabaseConnection, DbErr,
+    Statement,
+};
 use std::time::Duration;
 use tokio::fs;
 
+use crate::config::{DatabaseConfig, data_dir};
 use crate::info;
 
+pub mod backup;
+pub mod dialect;
+pub mod migrations;
 pub mod queries;
+pub mod store;
 pub mod utils;
 
-/// 初始化数据库连接
-pub async fn init() -> Result<DatabaseConnection, DbErr> {
-    if !Path::new("data").exists() {
-        let _ = fs::create_dir("data").await;
+/// 默认的 SQLite 数据库地址 (<数据目录>/bot.db)
+pub fn default_url() -> String {
+    // mode=rwc 允许 读/写/创建
+    format!("sqlite:{}?mode=rwc", data_dir().join("bot.db").display())
+}
+
+/// 隐藏连接地址中的密码，用于日志输出
+fn redact_url(url: &str) -> String {
+    match (url.find("://"), url.rfind('@')) {
+        (Some(scheme_end), Some(at)) if at > scheme_end => {
+            let cred = &url[scheme_end + 3..at];
+            match cred.split_once(':') {
+                Some((user, _)) => format!("{}{}:***{}", &url[..scheme_end + 3], user, &url[at..]),
+                None => url.to_string(),
+            }
+        }
+        _ => url.to_string(),
     }
+}
 
-    // mode=rwc 允许 读/写/创建
-    let db_url = "sqlite:data/bot.db?mode=rwc";
+/// 初始化数据库连接 (未配置 url 时使用 <数据目录>/bot.db)
+pub async fn init(config: &DatabaseConfig) -> Result<DatabaseConnection, DbErr> {
+    let dir = data_dir();
+    if !dir.exists() {
+        let _ = fs::create_dir_all(dir).await;
+    }
 
+    let db_url = if config.url.is_empty() {
+        default_url()
+    } else {
+        config.url.clone()
+    };
+
+    connect(&db_url, config.max_connections, config.min_connections).await
+}
+
+/// 按地址连接数据库，SQLite 额外开启 WAL 模式
+pub async fn connect(
+    db_url: &str,
+    max_connections: u32,
+    min_connections: u32,
+) -> Result<DatabaseConnection, DbErr> {
     // 配置连接池选项
-    let mut opt = ConnectOptions::new(db_url);
-    opt.max_connections(100)
-        .min_connections(5)
+    let mut opt = ConnectOptions::new(db_url.to_string());
+    opt.max_connections(max_connections)
+        .min_connections(min_connections.min(max_connections))
         .connect_timeout(Duration::from_secs(8))
         .acquire_timeout(Duration::from_secs(8))
         .idle_timeout(Duration::from_secs(8))
@@ -34,22 +74,26 @@ pub async fn init() -> Result<DatabaseConnection, DbErr> {
 
     let db = Database::connect(opt).await?;
 
-    // 开启 WAL 模式 (Write-Ahead Logging) 以提高并发性能
     let backend = db.get_database_backend();
-    db.execute(Statement::from_string(
-        backend,
-        "PRAGMA journal_mode=WAL;".to_owned(),
-    ))
-    .await?;
-
-    // 关闭过于严格的安全检查 (Synchronous NORMAL 足够安全且快)
-    db.execute(Statement::from_string(
-        backend,
-        "PRAGMA synchronous=NORMAL;".to_owned(),
-    ))
-    .await?;
-
-    info!(target: "Database", "连接成功: {} (WAL Mode)", db_url);
+    if backend == DatabaseBackend::Sqlite {
+        // 开启 WAL 模式 (Write-Ahead Logging) 以提高并发性能
+        db.execute(Statement::from_string(
+            backend,
+            "PRAGMA journal_mode=WAL;".to_owned(),
+        ))
+        .await?;
+
+        // 关闭过于严格的安全检查 (Synchronous NORMAL 足够安全且快)
+        db.execute(Statement::from_string(
+            backend,
+            "PRAGMA synchronous=NORMAL;".to_owned(),
+        ))
+        .await?;
+
+        info!(target: "Database", "连接成功: {} (WAL Mode)", db_url);
+    } else {
+        info!(target: "Database", "连接成功: {} ({:?})", redact_url(db_url), backend);
+    }
 
     Ok(db)
 }
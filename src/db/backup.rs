@@ -0,0 +1,207 @@
+use crate::plugins::recorder::entity::{self, Entity as MessageLogs};
+use chrono::{Local, TimeZone};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+type BackupError = Box<dyn std::error::Error + Send + Sync>;
+
+const SNAPSHOT_PREFIX: &str = "bot-";
+const SNAPSHOT_SUFFIX: &str = ".db.gz";
+
+/// 归档时每批读取的行数
+const ARCHIVE_BATCH: u64 = 1000;
+
+/// 使用 SQLite 在线备份 (VACUUM INTO) 生成一致性快照并 gzip 压缩，返回快照路径
+/// 非 SQLite 数据库返回 None，请使用 pg_dump / mysqldump 等工具备份
+pub async fn snapshot(db: &DatabaseConnection, dir: &Path) -> Result<Option<PathBuf>, BackupError> {
+    if db.get_database_backend() != DatabaseBackend::Sqlite {
+        return Ok(None);
+    }
+    tokio::fs::create_dir_all(dir).await?;
+
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    let raw = dir.join(format!("{}{}.db", SNAPSHOT_PREFIX, stamp));
+    let target = dir.join(format!("{}{}{}", SNAPSHOT_PREFIX, stamp, SNAPSHOT_SUFFIX));
+
+    // VACUUM INTO 要求目标文件不存在
+    let _ = tokio::fs::remove_file(&raw).await;
+    let escaped = raw.to_string_lossy().replace('\'', "''");
+    db.execute_unprepared(&format!("VACUUM INTO '{}'", escaped))
+        .await?;
+
+    let result = {
+        let (raw, target) = (raw.clone(), target.clone());
+        tokio::task::spawn_blocking(move || compress(&raw, &target)).await?
+    };
+    let _ = tokio::fs::remove_file(&raw).await;
+    result?;
+
+    Ok(Some(target))
+}
+
+fn compress(src: &Path, dst: &Path) -> io::Result<()> {
+    let tmp = dst.with_extension("gz.tmp");
+    let mut input = File::open(src)?;
+    let mut encoder = GzEncoder::new(File::create(&tmp)?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::rename(&tmp, dst)
+}
+
+/// 只保留最新的 keep 份快照 (0 表示全部保留)，返回删除的数量
+pub fn prune_snapshots(dir: &Path, keep: usize) -> io::Result<usize> {
+    if keep == 0 {
+        return Ok(0);
+    }
+
+    // 文件名中的时间戳可直接按字典序排序
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(SNAPSHOT_PREFIX) && n.ends_with(SNAPSHOT_SUFFIX))
+        })
+        .collect();
+    files.sort();
+
+    let excess = files.len().saturating_sub(keep);
+    for path in &files[..excess] {
+        fs::remove_file(path)?;
+    }
+    Ok(excess)
+}
+
+/// 将 time < cutoff 的聊天记录按月追加到 <dir>/messages-YYYY-MM.jsonl.gz，返回归档行数
+/// 每次归档在文件末尾追加一个新的 gzip 成员，可直接用 zcat / gzip -dc 读取全部内容
+/// 各月份先写入 .tmp 文件，全部写完后再替换正式文件，中途失败不会留下不完整的归档
+pub async fn archive_records(
+    db: &DatabaseConnection,
+    dir: &Path,
+    cutoff: i64,
+) -> Result<u64, BackupError> {
+    tokio::fs::create_dir_all(dir).await?;
+
+    // 文件与 gzip 写入在阻塞线程中进行，None 表示全部数据已读取完毕
+    let (tx, rx) = mpsc::channel::<Option<Vec<JsonValue>>>(2);
+    let writer = {
+        let dir = dir.to_path_buf();
+        tokio::task::spawn_blocking(move || write_archive(&dir, rx))
+    };
+
+    // 按 id 游标分页，避免 OFFSET 随页数增长变慢
+    let mut last_id = 0;
+    let read = async {
+        loop {
+            let rows = MessageLogs::find()
+                .filter(entity::Column::Time.lt(cutoff))
+                .filter(entity::Column::Id.gt(last_id))
+                .order_by_asc(entity::Column::Id)
+                .limit(ARCHIVE_BATCH)
+                .into_json()
+                .all(db)
+                .await?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            last_id = last.get("id").and_then(|v| v.as_i64()).unwrap_or(i64::MAX);
+            if tx.send(Some(rows)).await.is_err() {
+                // 写入线程已出错退出，错误由下方 writer 返回
+                break;
+            }
+        }
+        let _ = tx.send(None).await;
+        Ok::<(), BackupError>(())
+    }
+    .await;
+
+    // 先等待写入线程清理临时文件，再优先返回读取错误
+    drop(tx);
+    let written = writer.await?;
+    read?;
+    written
+}
+
+fn archive_path(dir: &Path, month: &str) -> PathBuf {
+    dir.join(format!("messages-{}.jsonl.gz", month))
+}
+
+fn write_archive(
+    dir: &Path,
+    mut rx: mpsc::Receiver<Option<Vec<JsonValue>>>,
+) -> Result<u64, BackupError> {
+    let mut writers: HashMap<String, GzEncoder<File>> = HashMap::new();
+    let mut temps: Vec<PathBuf> = Vec::new();
+
+    let result = write_batches(dir, &mut rx, &mut writers, &mut temps).and_then(|archived| {
+        for (_, writer) in writers.drain() {
+            writer.finish()?.sync_all()?;
+        }
+        for tmp in &temps {
+            fs::rename(tmp, tmp.with_extension(""))?;
+        }
+        Ok(archived)
+    });
+
+    if result.is_err() {
+        drop(writers);
+        for tmp in &temps {
+            let _ = fs::remove_file(tmp);
+        }
+    }
+    result
+}
+
+fn write_batches(
+    dir: &Path,
+    rx: &mut mpsc::Receiver<Option<Vec<JsonValue>>>,
+    writers: &mut HashMap<String, GzEncoder<File>>,
+    temps: &mut Vec<PathBuf>,
+) -> Result<u64, BackupError> {
+    let mut archived = 0;
+    loop {
+        let rows = match rx.blocking_recv() {
+            Some(Some(rows)) => rows,
+            Some(None) => return Ok(archived),
+            None => return Err("读取聊天记录中断，放弃本次归档".into()),
+        };
+
+        for row in rows {
+            let time = row.get("time").and_then(|t| t.as_i64()).unwrap_or(0);
+            let month = Local
+                .timestamp_opt(time, 0)
+                .single()
+                .map(|t| t.format("%Y-%m").to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+
+            let writer = match writers.entry(month) {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(e) => {
+                    // 复制已有归档到临时文件，再在其后追加新的 gzip 成员
+                    let path = archive_path(dir, e.key());
+                    let tmp = path.with_extension("gz.tmp");
+                    let mut file = File::create(&tmp)?;
+                    temps.push(tmp);
+                    if path.exists() {
+                        io::copy(&mut File::open(&path)?, &mut file)?;
+                    }
+                    e.insert(GzEncoder::new(file, Compression::default()))
+                }
+            };
+            writeln!(writer, "{}", row)?;
+            archived += 1;
+        }
+    }
+}
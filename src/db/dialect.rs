@@ -0,0 +1,69 @@
+use chrono::Local;
+use sea_orm::sea_query::{Expr, SimpleExpr};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, DbErr};
+
+/// 本地时区相对 UTC 的偏移 (秒)
+fn local_offset() -> i32 {
+    Local::now().offset().local_minus_utc()
+}
+
+/// 将 time 列 (秒级时间戳) 按本地时间格式化为聚合键
+/// by_minute 为 true 时输出 "HH:MM"，否则输出 "YYYY-MM-DD"
+pub fn local_time_key(backend: DatabaseBackend, by_minute: bool) -> SimpleExpr {
+    let sql = match backend {
+        DatabaseBackend::Sqlite => {
+            let fmt = if by_minute { "%H:%M" } else { "%Y-%m-%d" };
+            format!(
+                "strftime('{}', datetime(time, 'unixepoch', 'localtime'))",
+                fmt
+            )
+        }
+        DatabaseBackend::Postgres => {
+            let fmt = if by_minute { "HH24:MI" } else { "YYYY-MM-DD" };
+            format!(
+                "to_char(to_timestamp(\"time\" + {}) AT TIME ZONE 'UTC', '{}')",
+                local_offset(),
+                fmt
+            )
+        }
+        DatabaseBackend::MySql => {
+            let fmt = if by_minute { "%H:%i" } else { "%Y-%m-%d" };
+            format!(
+                "DATE_FORMAT(DATE_ADD('1970-01-01 00:00:00', INTERVAL (`time` + {}) SECOND), '{}')",
+                local_offset(),
+                fmt
+            )
+        }
+    };
+    Expr::cust(sql)
+}
+
+/// 整数求和 (MySQL 的 SUM 返回 DECIMAL，需转换为整数)
+pub fn int_sum(backend: DatabaseBackend, inner: &str) -> SimpleExpr {
+    match backend {
+        DatabaseBackend::MySql => Expr::cust(format!("CAST(SUM({}) AS SIGNED)", inner)),
+        _ => Expr::cust(format!("SUM({})", inner)),
+    }
+}
+
+/// 布尔列求和 (PostgreSQL 不支持直接对 boolean 求和)
+pub fn flag_sum(backend: DatabaseBackend, column: &str) -> SimpleExpr {
+    int_sum(backend, &flag_to_int(column))
+}
+
+/// 布尔列转 0/1
+pub fn flag_to_int(column: &str) -> String {
+    format!("CASE WHEN {} THEN 1 ELSE 0 END", column)
+}
+
+/// 整理数据库，回收已删除数据占用的空间；当前方言不支持时返回 false
+pub async fn vacuum(db: &DatabaseConnection) -> Result<bool, DbErr> {
+    let sql = match db.get_database_backend() {
+        DatabaseBackend::Sqlite => "VACUUM",
+        DatabaseBackend::Postgres => "VACUUM ANALYZE",
+        DatabaseBackend::MySql => return Ok(false),
+    };
+    // VACUUM 不能在事务 / 预处理语句中执行，使用非预处理方式
+    db.execute_unprepared(sql).await?;
+    Ok(true)
+}
@@ -0,0 +1,110 @@
+use crate::info;
+use chrono::Local;
+use futures_util::future::BoxFuture;
+use sea_orm::sea_query::IndexCreateStatement;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbErr,
+    EntityTrait, QueryFilter, Schema, Set, TransactionTrait,
+};
+
+mod entity {
+    use sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+    #[sea_orm(table_name = "schema_migrations")]
+    pub struct Model {
+        #[sea_orm(primary_key, auto_increment = false)]
+        pub plugin: String,
+        #[sea_orm(primary_key, auto_increment = false)]
+        pub version: i32,
+        pub name: String,
+        pub applied_at: i64,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+
+    impl ActiveModelBehavior for ActiveModel {}
+}
+
+pub type MigrationFn = for<'a> fn(&'a DatabaseTransaction) -> BoxFuture<'a, Result<(), DbErr>>;
+
+/// 一次数据库结构变更
+/// 每个插件的迁移按 version 递增排列，已发布的迁移不可修改，只能追加新版本
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub up: MigrationFn,
+}
+
+/// 根据实体创建表 (IF NOT EXISTS，兼容迁移机制引入前已存在的表)
+pub async fn create_table<C, E>(db: &C, entity: E) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    E: EntityTrait,
+{
+    let builder = db.get_database_backend();
+    let schema = Schema::new(builder);
+    let mut stmt = schema.create_table_from_entity(entity);
+    db.execute(builder.build(stmt.if_not_exists())).await?;
+    Ok(())
+}
+
+/// 创建索引 (调用方需设置 if_not_exists)
+pub async fn create_index<C>(db: &C, stmt: IndexCreateStatement) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+{
+    let builder = db.get_database_backend();
+    db.execute(builder.build(&stmt)).await?;
+    Ok(())
+}
+
+/// 执行尚未应用的迁移，每个迁移在独立事务中执行并记录到 schema_migrations
+/// 返回本次应用的迁移数量
+pub async fn run(
+    db: &DatabaseConnection,
+    sources: &[(&str, &'static [Migration])],
+) -> Result<usize, DbErr> {
+    create_table(db, entity::Entity).await?;
+
+    let mut applied_count = 0;
+
+    for (owner, migrations) in sources {
+        let applied: Vec<i32> = entity::Entity::find()
+            .filter(entity::Column::Plugin.eq(*owner))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| m.version)
+            .collect();
+
+        for m in migrations.iter() {
+            if applied.contains(&m.version) {
+                continue;
+            }
+
+            let txn = db.begin().await?;
+            (m.up)(&txn).await.map_err(|e| {
+                DbErr::Custom(format!(
+                    "迁移 [{}] v{} {} 失败: {}",
+                    owner, m.version, m.name, e
+                ))
+            })?;
+            entity::ActiveModel {
+                plugin: Set(owner.to_string()),
+                version: Set(m.version),
+                name: Set(m.name.to_string()),
+                applied_at: Set(Local::now().timestamp()),
+            }
+            .insert(&txn)
+            .await?;
+            txn.commit().await?;
+
+            info!(target: "Database", "已应用迁移 [{}] v{} {}", owner, m.version, m.name);
+            applied_count += 1;
+        }
+    }
+
+    Ok(applied_count)
+}
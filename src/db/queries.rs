@@ -1,16 +1,24 @@
+use super::dialect::{flag_sum, flag_to_int, int_sum, local_time_key};
 use crate::plugins::recorder::entity::{self, Entity as MessageLogs};
-use sea_orm::sea_query::{Alias, Expr, Func, SimpleExpr};
+use crate::plugins::recorder::fts;
+use crate::plugins::recorder::interactions::entity::{
+    Column as InteractionColumn, Entity as Interactions,
+};
+use crate::plugins::recorder::privacy;
+use sea_orm::sea_query::{Alias, Expr};
 use sea_orm::{
-    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, PaginatorTrait,
-    QueryFilter, QueryOrder, QuerySelect,
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    FromQueryResult, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect,
 };
 
 // ================= 常量定义 =================
 
 const MAX_TEXT_CORPUS_LIMIT: u64 = 20000;
+const DAILY_CORPUS_PAGE_SIZE: u64 = 5000;
 const MAX_RANKING_LIMIT: u64 = 50;
 const MAX_TREND_LIMIT: u64 = 2000;
 const MAX_GROUP_TREND_LIMIT: u64 = 10000;
+const MAX_INTERACTION_EDGE_LIMIT: u64 = 5000;
 
 // ================= 数据结构 =================
 
@@ -20,6 +28,14 @@ pub struct TextData {
     pub content_text: String,
 }
 
+/// 带日期的纯文本数据（用于热词、话题趋势）
+#[derive(Debug, FromQueryResult)]
+pub struct DailyText {
+    pub id: i32,
+    pub date: String,
+    pub content_text: String,
+}
+
 /// 用户活跃排行（龙王榜）
 #[derive(Debug, FromQueryResult)]
 pub struct UserRanking {
@@ -85,6 +101,21 @@ pub struct HeatmapData {
     pub count: i64,
 }
 
+/// 用户之间的互动次数 (回复 + @，有方向)
+#[derive(Debug, FromQueryResult)]
+pub struct InteractionEdge {
+    pub user_id: i64,
+    pub target_id: i64,
+    pub count: i64,
+}
+
+/// 用户昵称
+#[derive(Debug, FromQueryResult)]
+pub struct UserNickname {
+    pub user_id: i64,
+    pub nickname: String,
+}
+
 /// 消息类型统计
 #[derive(Debug, FromQueryResult)]
 pub struct MessageTypeStats {
@@ -98,6 +129,16 @@ pub struct MessageTypeStats {
 
 // ================= 查询函数 =================
 
+/// 排除已选择不被记录的用户 (用于排行、词云等聚合统计)
+fn exclude_opted_out<Q: QueryFilter, C: ColumnTrait>(query: Q, column: C) -> Q {
+    let users = privacy::opted_out_users();
+    if users.is_empty() {
+        query
+    } else {
+        query.filter(column.is_not_in(users))
+    }
+}
+
 /// 获取指定时间范围内的纯文本内容列表
 pub async fn get_text_corpus(
     db: &DatabaseConnection,
@@ -119,6 +160,7 @@ pub async fn get_text_corpus(
     if let Some(uid) = user_id {
         query = query.filter(entity::Column::UserId.eq(uid));
     }
+    query = exclude_opted_out(query, entity::Column::UserId);
 
     let results: Vec<TextData> = query
         .limit(MAX_TEXT_CORPUS_LIMIT)
@@ -128,6 +170,47 @@ pub async fn get_text_corpus(
     Ok(results.into_iter().map(|d| d.content_text).collect())
 }
 
+/// 获取指定时间范围内带本地日期的分词文本中 id 大于 after_id 的一页
+/// 按 id 游标分页，调用方循环调用直到返回空即可遍历整个时间段，不受条数上限影响
+/// keyword 不为空时只返回分词结果中包含该关键词的消息
+pub async fn get_daily_corpus_page(
+    db: &DatabaseConnection,
+    group_id: Option<i64>,
+    user_id: Option<i64>,
+    keyword: Option<&str>,
+    start_time: i64,
+    end_time: i64,
+    after_id: i32,
+) -> Result<Vec<DailyText>, DbErr> {
+    let mut query = MessageLogs::find()
+        .select_only()
+        .column(entity::Column::Id)
+        .column_as(local_time_key(db.get_database_backend(), false), "date")
+        .column_as(entity::Column::Tokens, "content_text")
+        .filter(entity::Column::Time.gte(start_time))
+        .filter(entity::Column::Time.lt(end_time))
+        .filter(entity::Column::Tokens.ne(""))
+        .filter(entity::Column::Id.gt(after_id));
+
+    if let Some(gid) = group_id {
+        query = query.filter(entity::Column::GroupId.eq(gid));
+    }
+    if let Some(uid) = user_id {
+        query = query.filter(entity::Column::UserId.eq(uid));
+    }
+    if let Some(kw) = keyword {
+        query = query.filter(entity::Column::Tokens.contains(kw));
+    }
+    query = exclude_opted_out(query, entity::Column::UserId);
+
+    query
+        .order_by_asc(entity::Column::Id)
+        .limit(DAILY_CORPUS_PAGE_SIZE)
+        .into_model::<DailyText>()
+        .all(db)
+        .await
+}
+
 /// 获取活跃用户排行（龙王榜）
 pub async fn get_user_ranking(
     db: &DatabaseConnection,
@@ -147,6 +230,7 @@ pub async fn get_user_ranking(
     if let Some(gid) = group_id {
         query = query.filter(entity::Column::GroupId.eq(gid));
     }
+    query = exclude_opted_out(query, entity::Column::UserId);
 
     query
         .group_by(entity::Column::UserId)
@@ -213,11 +297,7 @@ pub async fn get_user_emoji_ranking(
     end_time: i64,
     limit: u64,
 ) -> Result<Vec<UserRanking>, DbErr> {
-    let count_expr = Func::cast_as(
-        Expr::col(entity::Column::IsAnimEmoji),
-        Alias::new("integer"),
-    );
-    let sum_expr = SimpleExpr::from(Func::sum(count_expr));
+    let sum_expr = flag_sum(db.get_database_backend(), "is_anim_emoji");
 
     let mut query = MessageLogs::find()
         .select_only()
@@ -230,6 +310,7 @@ pub async fn get_user_emoji_ranking(
     if let Some(gid) = group_id {
         query = query.filter(entity::Column::GroupId.eq(gid));
     }
+    query = exclude_opted_out(query, entity::Column::UserId);
 
     query
         .group_by(entity::Column::UserId)
@@ -248,7 +329,7 @@ pub async fn get_daily_trend(
     start_time: i64,
     end_time: i64,
 ) -> Result<Vec<DailyTrend>, DbErr> {
-    let date_expr = Expr::cust("strftime('%Y-%m-%d', datetime(time, 'unixepoch', 'localtime'))");
+    let date_expr = local_time_key(db.get_database_backend(), false);
 
     let mut query = MessageLogs::find()
         .select_only()
@@ -280,16 +361,12 @@ pub async fn get_daily_trend_by_group(
     end_time: i64,
     by_hour: bool,
 ) -> Result<Vec<GroupTrend>, DbErr> {
-    let time_expr = if by_hour {
-        Expr::cust("strftime('%H:%M', datetime(time, 'unixepoch', 'localtime'))")
-    } else {
-        Expr::cust("strftime('%Y-%m-%d', datetime(time, 'unixepoch', 'localtime'))")
-    };
+    let time_expr = local_time_key(db.get_database_backend(), by_hour);
 
     MessageLogs::find()
         .select_only()
         .column_as(time_expr.clone(), "date")
-        .column(entity::Column::GroupName)
+        .column_as(Expr::col(entity::Column::GroupName).max(), "group_name")
         .column_as(Expr::col(entity::Column::Id).count(), "count")
         .filter(entity::Column::Time.gte(start_time))
         .filter(entity::Column::Time.lt(end_time))
@@ -312,26 +389,27 @@ pub async fn get_message_type_trend(
     end_time: i64,
     by_hour: bool,
 ) -> Result<Vec<MessageTypeTrend>, DbErr> {
+    let backend = db.get_database_backend();
+
     // 聚合时间键 (按小时 或 按天)
-    let time_expr = if by_hour {
-        Expr::cust("strftime('%H:%M', datetime(time, 'unixepoch', 'localtime'))")
-    } else {
-        Expr::cust("strftime('%Y-%m-%d', datetime(time, 'unixepoch', 'localtime'))")
-    };
+    let time_expr = local_time_key(backend, by_hour);
 
     // 统计逻辑定义：
     // 1. 文本: length > 0
-    let text_expr = Expr::cust("SUM(CASE WHEN length > 0 THEN 1 ELSE 0 END)");
+    let text_expr = int_sum(backend, "CASE WHEN length > 0 THEN 1 ELSE 0 END");
     // 2. 动画表情: is_anim_emoji = true
-    let anim_expr = Expr::col(entity::Column::IsAnimEmoji).sum();
+    let anim_expr = flag_sum(backend, "is_anim_emoji");
     // 3. 图片: image_count - is_anim_emoji
-    let image_expr = Expr::cust("SUM(image_count) - SUM(is_anim_emoji)");
+    let image_expr = int_sum(
+        backend,
+        &format!("image_count - {}", flag_to_int("is_anim_emoji")),
+    );
     // 4. 语音: is_voice
-    let voice_expr = Expr::col(entity::Column::IsVoice).sum();
+    let voice_expr = flag_sum(backend, "is_voice");
     // 5. 视频: is_video
-    let video_expr = Expr::col(entity::Column::IsVideo).sum();
+    let video_expr = flag_sum(backend, "is_video");
     // 6. 表情 (Face): face_count
-    let face_expr = Expr::col(entity::Column::FaceCount).sum();
+    let face_expr = int_sum(backend, "face_count");
 
     let mut query = MessageLogs::find()
         .select_only()
@@ -421,6 +499,7 @@ pub async fn get_weekday_activity(
 pub async fn get_heatmap_data(
     db: &DatabaseConnection,
     group_id: Option<i64>,
+    user_id: Option<i64>,
     start_time: i64,
     end_time: i64,
 ) -> Result<Vec<HeatmapData>, DbErr> {
@@ -435,6 +514,9 @@ pub async fn get_heatmap_data(
     if let Some(gid) = group_id {
         query = query.filter(entity::Column::GroupId.eq(gid));
     }
+    if let Some(uid) = user_id {
+        query = query.filter(entity::Column::UserId.eq(uid));
+    }
 
     query
         .group_by(entity::Column::TimeWeekday)
@@ -452,12 +534,16 @@ pub async fn get_message_type_stats(
     start_time: i64,
     end_time: i64,
 ) -> Result<MessageTypeStats, DbErr> {
-    let text_expr = Expr::cust("SUM(CASE WHEN length > 0 THEN 1 ELSE 0 END)");
-    let anim_expr = Expr::col(entity::Column::IsAnimEmoji).sum();
-    let image_expr = Expr::cust("SUM(image_count) - SUM(is_anim_emoji)");
-    let voice_expr = Expr::col(entity::Column::IsVoice).sum();
-    let video_expr = Expr::col(entity::Column::IsVideo).sum();
-    let face_expr = Expr::col(entity::Column::FaceCount).sum();
+    let backend = db.get_database_backend();
+    let text_expr = int_sum(backend, "CASE WHEN length > 0 THEN 1 ELSE 0 END");
+    let anim_expr = flag_sum(backend, "is_anim_emoji");
+    let image_expr = int_sum(
+        backend,
+        &format!("image_count - {}", flag_to_int("is_anim_emoji")),
+    );
+    let voice_expr = flag_sum(backend, "is_voice");
+    let video_expr = flag_sum(backend, "is_video");
+    let face_expr = int_sum(backend, "face_count");
 
     let mut query = MessageLogs::find()
         .select_only()
@@ -510,3 +596,152 @@ pub async fn get_message_count(
 
     query.count(db).await
 }
+
+/// 获取时间范围内最早的一条消息
+pub async fn get_first_message(
+    db: &DatabaseConnection,
+    group_id: Option<i64>,
+    user_id: Option<i64>,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Option<entity::Model>, DbErr> {
+    let mut query = MessageLogs::find()
+        .filter(entity::Column::Time.gte(start_time))
+        .filter(entity::Column::Time.lt(end_time));
+
+    if let Some(gid) = group_id {
+        query = query.filter(entity::Column::GroupId.eq(gid));
+    }
+    if let Some(uid) = user_id {
+        query = query.filter(entity::Column::UserId.eq(uid));
+    }
+
+    query
+        .order_by_asc(entity::Column::Time)
+        .order_by_asc(entity::Column::Id)
+        .one(db)
+        .await
+}
+
+/// 获取用户之间的互动次数 (按次数降序)
+/// user_id 不为空时只返回该用户发起或收到的互动
+pub async fn get_interaction_edges(
+    db: &DatabaseConnection,
+    group_id: Option<i64>,
+    user_id: Option<i64>,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Vec<InteractionEdge>, DbErr> {
+    let mut query = Interactions::find()
+        .select_only()
+        .column(InteractionColumn::UserId)
+        .column(InteractionColumn::TargetId)
+        .column_as(Expr::col(InteractionColumn::Id).count(), "count")
+        .filter(InteractionColumn::Time.gte(start_time))
+        .filter(InteractionColumn::Time.lt(end_time));
+
+    if let Some(gid) = group_id {
+        query = query.filter(InteractionColumn::GroupId.eq(gid));
+    }
+    if let Some(uid) = user_id {
+        query = query.filter(
+            Condition::any()
+                .add(InteractionColumn::UserId.eq(uid))
+                .add(InteractionColumn::TargetId.eq(uid)),
+        );
+    }
+    query = exclude_opted_out(query, InteractionColumn::UserId);
+    query = exclude_opted_out(query, InteractionColumn::TargetId);
+
+    query
+        .group_by(InteractionColumn::UserId)
+        .group_by(InteractionColumn::TargetId)
+        .order_by_desc(Expr::custom_keyword(Alias::new("count")))
+        .limit(MAX_INTERACTION_EDGE_LIMIT)
+        .into_model::<InteractionEdge>()
+        .all(db)
+        .await
+}
+
+/// 批量获取用户昵称 (群名片优先)
+pub async fn get_user_nicknames(
+    db: &DatabaseConnection,
+    group_id: Option<i64>,
+    user_ids: &[i64],
+) -> Result<Vec<UserNickname>, DbErr> {
+    if user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query = MessageLogs::find()
+        .select_only()
+        .column(entity::Column::UserId)
+        .column_as(Expr::col(entity::Column::SenderNick).max(), "nickname")
+        .filter(entity::Column::UserId.is_in(user_ids.iter().copied()));
+
+    if let Some(gid) = group_id {
+        query = query.filter(entity::Column::GroupId.eq(gid));
+    }
+
+    query
+        .group_by(entity::Column::UserId)
+        .into_model::<UserNickname>()
+        .all(db)
+        .await
+}
+
+/// 全文搜索聊天记录 (按时间倒序分页，page 从 0 开始)，返回 (当前页记录, 总条数)
+/// words 为分词后的关键词，需全部命中
+#[allow(clippy::too_many_arguments)]
+pub async fn search_messages(
+    db: &DatabaseConnection,
+    group_id: Option<i64>,
+    user_id: Option<i64>,
+    words: &[String],
+    start_time: i64,
+    end_time: i64,
+    page: u64,
+    page_size: u64,
+) -> Result<(Vec<entity::Model>, u64), DbErr> {
+    let mut query = MessageLogs::find()
+        .filter(fts::match_condition(db.get_database_backend(), words))
+        .filter(entity::Column::Time.gte(start_time))
+        .filter(entity::Column::Time.lt(end_time));
+
+    if let Some(gid) = group_id {
+        query = query.filter(entity::Column::GroupId.eq(gid));
+    }
+    if let Some(uid) = user_id {
+        query = query.filter(entity::Column::UserId.eq(uid));
+    }
+
+    let paginator = query
+        .order_by_desc(entity::Column::Time)
+        .order_by_desc(entity::Column::Id)
+        .paginate(db, page_size.max(1));
+
+    let total = paginator.num_items().await?;
+    let rows = paginator.fetch_page(page).await?;
+    Ok((rows, total))
+}
+
+/// 按 id 顺序分批读取群聊记录 (游标分页)，返回 id 大于 after_id 的至多 limit 条
+/// 用于导出等需要遍历大范围数据的场景，避免一次性载入内存
+pub async fn get_group_messages_after(
+    db: &DatabaseConnection,
+    group_id: i64,
+    start_time: i64,
+    end_time: i64,
+    after_id: i32,
+    limit: u64,
+) -> Result<Vec<entity::Model>, DbErr> {
+    MessageLogs::find()
+        .filter(entity::Column::GroupId.eq(group_id))
+        .filter(entity::Column::Time.gte(start_time))
+        .filter(entity::Column::Time.lt(end_time))
+        .filter(entity::Column::Id.gt(after_id))
+        .order_by_asc(entity::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await
+}
@@ -0,0 +1,218 @@
+use super::migrations::{Migration, create_index, create_table};
+use chrono::Local;
+use futures_util::future::BoxFuture;
+use sea_orm::sea_query::{Index, LikeExpr, OnConflict};
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use std::time::Duration;
+use tokio::sync::Mutex as AsyncMutex;
+
+pub mod entity {
+    use sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+    #[sea_orm(table_name = "plugin_kv")]
+    pub struct Model {
+        #[sea_orm(primary_key, auto_increment = false)]
+        pub namespace: String,
+        #[sea_orm(primary_key, auto_increment = false)]
+        pub key: String,
+        #[sea_orm(column_type = "Text")]
+        pub value: String, // JSON
+        pub expires_at: Option<i64>,
+        pub updated_at: i64,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+
+    impl ActiveModelBehavior for ActiveModel {}
+}
+
+use entity::Entity as Kv;
+
+pub type StoreError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 框架自身的迁移 (以 "store" 名义记录在 schema_migrations 中)
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    name: "create_plugin_kv",
+    up: migrate_create_kv,
+}];
+
+fn migrate_create_kv(db: &DatabaseTransaction) -> BoxFuture<'_, Result<(), DbErr>> {
+    Box::pin(async move {
+        create_table(db, Kv).await?;
+        create_index(
+            db,
+            Index::create()
+                .name("idx_plugin_kv_expires_at")
+                .table(Kv)
+                .col(entity::Column::ExpiresAt)
+                .if_not_exists()
+                .to_owned(),
+        )
+        .await
+    })
+}
+
+/// 序列化 update 操作，避免并发的 读-改-写 互相覆盖
+static UPDATE_LOCK: AsyncMutex<()> = AsyncMutex::const_new(());
+
+/// 按插件隔离的键值存储，值以 JSON 序列化保存
+/// 通过 `ctx.store("插件名")` 获取
+#[derive(Clone)]
+pub struct Store {
+    db: DatabaseConnection,
+    namespace: String,
+}
+
+impl Store {
+    pub fn new(db: DatabaseConnection, namespace: &str) -> Self {
+        Self {
+            db,
+            namespace: namespace.to_string(),
+        }
+    }
+
+    /// 未过期的条件
+    fn alive() -> Condition {
+        Condition::any()
+            .add(entity::Column::ExpiresAt.is_null())
+            .add(entity::Column::ExpiresAt.gt(Local::now().timestamp()))
+    }
+
+    fn find(&self, key: &str) -> sea_orm::Select<Kv> {
+        Kv::find()
+            .filter(entity::Column::Namespace.eq(&self.namespace))
+            .filter(entity::Column::Key.eq(key))
+            .filter(Self::alive())
+    }
+
+    /// 读取值，不存在或已过期时返回 None
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, StoreError> {
+        match self.find(key).one(&self.db).await? {
+            Some(m) => Ok(Some(serde_json::from_str(&m.value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 写入值 (永不过期)
+    pub async fn set<T: Serialize>(&self, key: &str, value: &T) -> Result<(), StoreError> {
+        self.put(&self.db, key, value, None).await
+    }
+
+    /// 写入值，ttl 之后视为不存在
+    pub async fn set_with_ttl<T: Serialize>(
+        &self,
+        key: &str,
+        value: &T,
+        ttl: Duration,
+    ) -> Result<(), StoreError> {
+        let expires_at = Local::now().timestamp() + ttl.as_secs() as i64;
+        self.put(&self.db, key, value, Some(expires_at)).await
+    }
+
+    async fn put<C, T>(
+        &self,
+        db: &C,
+        key: &str,
+        value: &T,
+        expires_at: Option<i64>,
+    ) -> Result<(), StoreError>
+    where
+        C: sea_orm::ConnectionTrait,
+        T: Serialize,
+    {
+        let model = entity::ActiveModel {
+            namespace: Set(self.namespace.clone()),
+            key: Set(key.to_string()),
+            value: Set(serde_json::to_string(value)?),
+            expires_at: Set(expires_at),
+            updated_at: Set(Local::now().timestamp()),
+        };
+
+        Kv::insert(model)
+            .on_conflict(
+                OnConflict::columns([entity::Column::Namespace, entity::Column::Key])
+                    .update_columns([
+                        entity::Column::Value,
+                        entity::Column::ExpiresAt,
+                        entity::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+        Ok(())
+    }
+
+    /// 删除键，返回是否删除了未过期的值
+    pub async fn delete(&self, key: &str) -> Result<bool, StoreError> {
+        let existed = self.find(key).one(&self.db).await?.is_some();
+        Kv::delete_many()
+            .filter(entity::Column::Namespace.eq(&self.namespace))
+            .filter(entity::Column::Key.eq(key))
+            .exec(&self.db)
+            .await?;
+        Ok(existed)
+    }
+
+    /// 按前缀列出未过期的键值对 (按键排序)，前缀为空时列出全部
+    pub async fn list<T: DeserializeOwned>(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<(String, T)>, StoreError> {
+        let escaped = prefix
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+
+        let rows = Kv::find()
+            .filter(entity::Column::Namespace.eq(&self.namespace))
+            .filter(entity::Column::Key.like(LikeExpr::new(format!("{}%", escaped)).escape('\\')))
+            .filter(Self::alive())
+            .order_by_asc(entity::Column::Key)
+            .all(&self.db)
+            .await?;
+
+        rows.into_iter()
+            .map(|m| Ok((m.key, serde_json::from_str(&m.value)?)))
+            .collect()
+    }
+
+    /// 原子地读取-修改-写入，f 接收当前值 (不存在或已过期时为 None) 并返回新值
+    /// 原有的过期时间保持不变
+    pub async fn update<T, F>(&self, key: &str, f: F) -> Result<T, StoreError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce(Option<T>) -> T,
+    {
+        let _guard = UPDATE_LOCK.lock().await;
+        let txn = self.db.begin().await?;
+
+        let current = self.find(key).one(&txn).await?;
+        let expires_at = current.as_ref().and_then(|m| m.expires_at);
+        let old = match current {
+            Some(m) => Some(serde_json::from_str(&m.value)?),
+            None => None,
+        };
+
+        let new = f(old);
+        self.put(&txn, key, &new, expires_at).await?;
+        txn.commit().await?;
+        Ok(new)
+    }
+}
+
+/// 清理所有已过期的键，返回删除数量
+pub async fn purge_expired(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let res = Kv::delete_many()
+        .filter(entity::Column::ExpiresAt.lte(Local::now().timestamp()))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
@@ -1,110 +1,167 @@
-use chrono::{Datelike, Duration, Local};
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, TimeZone};
+use regex::Regex;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// 一周的第一天 (距周一的天数，0 = 周一，6 = 周日)
+static WEEK_START: AtomicU32 = AtomicU32::new(0);
+
+/// 设置一周的第一天 (1 = 周一 … 7 = 周日，超出范围按周一处理)
+pub fn set_week_start(day: u32) {
+    let offset = if (1..=7).contains(&day) { day - 1 } else { 0 };
+    WEEK_START.store(offset, Ordering::Relaxed);
+}
+
+/// 日期 / 月份 / 年份: 2025-03-05、2025/3/5、2025年3月5日、2025-03、2025年3月、2025年
+const TIME_POINT: &str =
+    r"\d{4}(?:[-/]\d{1,2}(?:[-/]\d{1,2})?|年(?:\d{1,2}月(?:\d{1,2}[日号]?)?)?)";
+
+static TIME_EXPR: OnceLock<String> = OnceLock::new();
+static TIME_EXPR_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// 时间表达式的正则片段 (不含捕获组)，供各插件拼接指令正则
+/// 支持: 今日/昨日/前天/本周/上周/本月/上月/今年/去年/总、近N小时/天/周/月/年、
+/// 单个日期/月份/年份，以及用 到/至/~ 连接的区间
+pub fn time_expr_pattern() -> &'static str {
+    TIME_EXPR.get_or_init(|| {
+        format!(
+            r"(?:今日|今天|昨日|昨天|前天|本周|上周|本月|上月|今年|去年|总|近\d+(?:小时|天|周|个?月|年)|{p}(?:(?:到|至|~){p})?)",
+            p = TIME_POINT
+        )
+    })
+}
 
 /// 根据自然语言时间描述获取时间戳范围 (start, end)
 ///
 /// 适用场景：生成词云、查询统计数据等
-/// 支持：今日, 昨日, 本周, 上周, 近7天, 近30天, 本月, 上月, 今年, 去年, 总
-/// 默认返回今日范围
+/// 无法识别的描述返回今日范围，需要区分时请使用 [`parse_time_range`]
 pub fn get_time_range(time_str: &str) -> (i64, i64) {
+    parse_time_range(time_str).unwrap_or_else(|| {
+        let now = Local::now();
+        (day_start(now.date_naive()).timestamp(), now.timestamp())
+    })
+}
+
+/// 解析时间表达式，无法识别时返回 None
+/// 结束时间不会超过当前时间
+pub fn parse_time_range(time_str: &str) -> Option<(i64, i64)> {
     let now = Local::now();
-    let today_start = now
-        .date_naive()
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .and_local_timezone(Local)
-        .unwrap();
-
-    match time_str {
-        "今日" => (today_start.timestamp(), now.timestamp()),
-        "昨日" => {
-            let yest_start = today_start - Duration::days(1);
-            (yest_start.timestamp(), today_start.timestamp())
-        }
-        "本周" => {
-            let weekday = now.weekday().num_days_from_monday();
-            let week_start = today_start - Duration::days(weekday as i64);
-            (week_start.timestamp(), now.timestamp())
-        }
+    let today = now.date_naive();
+    let today_start = day_start(today);
+
+    let (start, end) = match time_str.trim() {
+        "今日" | "今天" => (today_start, now),
+        "昨日" | "昨天" => (today_start - Duration::days(1), today_start),
+        "前天" => (
+            today_start - Duration::days(2),
+            today_start - Duration::days(1),
+        ),
+        "本周" => (week_start(today), now),
         "上周" => {
-            let weekday = now.weekday().num_days_from_monday();
-            let this_week_start = today_start - Duration::days(weekday as i64);
-            let last_week_start = this_week_start - Duration::days(7);
-            (last_week_start.timestamp(), this_week_start.timestamp())
-        }
-        "近7天" => {
-            let start = now - Duration::days(7);
-            (start.timestamp(), now.timestamp())
-        }
-        "近30天" => {
-            let start = now - Duration::days(30);
-            (start.timestamp(), now.timestamp())
-        }
-        "本月" => {
-            let month_start = now
-                .date_naive()
-                .with_day(1)
-                .unwrap()
-                .and_hms_opt(0, 0, 0)
-                .unwrap()
-                .and_local_timezone(Local)
-                .unwrap();
-            (month_start.timestamp(), now.timestamp())
+            let this_week_start = week_start(today);
+            (this_week_start - Duration::days(7), this_week_start)
         }
+        "本月" => (day_start(today.with_day(1)?), now),
         "上月" => {
-            let this_month_start = now
-                .date_naive()
-                .with_day(1)
-                .unwrap()
-                .and_hms_opt(0, 0, 0)
-                .unwrap()
-                .and_local_timezone(Local)
-                .unwrap();
-
-            let (prev_year, prev_month) = if this_month_start.month() == 1 {
-                (this_month_start.year() - 1, 12)
-            } else {
-                (this_month_start.year(), this_month_start.month() - 1)
-            };
-
-            let prev_month_start = this_month_start
-                .with_year(prev_year)
-                .unwrap()
-                .with_month(prev_month)
-                .unwrap();
-
-            (prev_month_start.timestamp(), this_month_start.timestamp())
+            let this_month = today.with_day(1)?;
+            (
+                day_start(this_month - Months::new(1)),
+                day_start(this_month),
+            )
+        }
+        "今年" => (day_start(NaiveDate::from_ymd_opt(today.year(), 1, 1)?), now),
+        "去年" => (
+            day_start(NaiveDate::from_ymd_opt(today.year() - 1, 1, 1)?),
+            day_start(NaiveDate::from_ymd_opt(today.year(), 1, 1)?),
+        ),
+        "总" => return Some((0, now.timestamp())),
+        s => parse_relative(s, now).or_else(|| parse_span(s))?,
+    };
+
+    let end = end.min(now);
+    Some((start.timestamp(), end.timestamp().max(start.timestamp())))
+}
+
+fn time_expr_regex() -> &'static Regex {
+    TIME_EXPR_REGEX.get_or_init(|| {
+        Regex::new(&format!(
+            r"^(?:近(\d+)(小时|天|周|个?月|年)|({p})(?:(?:到|至|~)({p}))?)$",
+            p = TIME_POINT
+        ))
+        .unwrap()
+    })
+}
+
+/// 近N小时/天/周/月/年
+fn parse_relative(s: &str, now: DateTime<Local>) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let caps = time_expr_regex().captures(s)?;
+    let n: u32 = caps.get(1)?.as_str().parse().ok()?;
+
+    let start = match caps.get(2)?.as_str() {
+        "小时" => now.checked_sub_signed(Duration::try_hours(n as i64)?)?,
+        "天" => now.checked_sub_signed(Duration::try_days(n as i64)?)?,
+        "周" => now.checked_sub_signed(Duration::try_weeks(n as i64)?)?,
+        "月" | "个月" => now.checked_sub_months(Months::new(n))?,
+        "年" => now.checked_sub_months(Months::new(n.checked_mul(12)?))?,
+        _ => return None,
+    };
+    Some((start, now))
+}
+
+/// 单个日期/月份/年份，或 "起点到终点" 区间 (终点包含在内)
+fn parse_span(s: &str) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let caps = time_expr_regex().captures(s)?;
+    let (from_start, from_end) = parse_point(caps.get(3)?.as_str())?;
+
+    let (start, end) = match caps.get(4) {
+        Some(m) => {
+            let (to_start, to_end) = parse_point(m.as_str())?;
+            // 起止颠倒时自动交换
+            (from_start.min(to_start), from_end.max(to_end))
         }
-        "今年" => {
-            let year_start = now
-                .date_naive()
-                .with_month(1)
-                .unwrap()
-                .with_day(1)
-                .unwrap()
-                .and_hms_opt(0, 0, 0)
-                .unwrap()
-                .and_local_timezone(Local)
-                .unwrap();
-            (year_start.timestamp(), now.timestamp())
+        None => (from_start, from_end),
+    };
+    Some((day_start(start), day_start(end)))
+}
+
+/// 解析时间点，返回 [起始日期, 结束日期) 的左闭右开区间
+fn parse_point(s: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let nums: Vec<u32> = s
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+
+    let year = *nums.first()? as i32;
+    match nums.len() {
+        1 => Some((
+            NaiveDate::from_ymd_opt(year, 1, 1)?,
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?,
+        )),
+        2 => {
+            let start = NaiveDate::from_ymd_opt(year, nums[1], 1)?;
+            Some((start, start + Months::new(1)))
         }
-        "去年" => {
-            let this_year_start = now
-                .date_naive()
-                .with_month(1)
-                .unwrap()
-                .with_day(1)
-                .unwrap()
-                .and_hms_opt(0, 0, 0)
-                .unwrap()
-                .and_local_timezone(Local)
-                .unwrap();
-
-            let last_year_start = this_year_start
-                .with_year(this_year_start.year() - 1)
-                .unwrap();
-            (last_year_start.timestamp(), this_year_start.timestamp())
+        3 => {
+            let day = NaiveDate::from_ymd_opt(year, nums[1], nums[2])?;
+            Some((day, day.succ_opt()?))
         }
-        "总" => (0, now.timestamp()),
-        _ => (today_start.timestamp(), now.timestamp()),
+        _ => None,
     }
 }
+
+/// 本地时区某日零点
+fn day_start(date: NaiveDate) -> DateTime<Local> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .unwrap_or_else(|| Local.from_utc_datetime(&midnight))
+}
+
+/// 按配置的一周起始日计算本周第一天零点
+fn week_start(today: NaiveDate) -> DateTime<Local> {
+    let offset = WEEK_START.load(Ordering::Relaxed);
+    let days = (today.weekday().num_days_from_monday() + 7 - offset) % 7;
+    day_start(today - Duration::days(days as i64))
+}
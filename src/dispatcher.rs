@@ -0,0 +1,146 @@
+use crate::error;
+use crate::event::Event;
+use futures_util::FutureExt;
+use futures_util::future::BoxFuture;
+use simd_json::derived::ValueObjectAccessAsScalar;
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// 频道队列空闲多久后回收工作协程
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub type Job = BoxFuture<'static, ()>;
+
+/// 事件分发器
+/// - 同一频道 (群/私聊) 的事件按到达顺序串行处理
+/// - 不同频道之间并行，总并发受全局信号量限制
+/// - 每个频道队列有上限，洪泛时丢弃新事件而不是无限堆积任务
+pub struct Dispatcher {
+    semaphore: Arc<Semaphore>,
+    queues: Mutex<HashMap<String, mpsc::Sender<Job>>>,
+    queue_size: usize,
+}
+
+static DISPATCHER: OnceLock<Arc<Dispatcher>> = OnceLock::new();
+
+impl Dispatcher {
+    pub fn new(max_concurrency: usize, queue_size: usize) -> Arc<Self> {
+        Arc::new(Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrency.max(1))),
+            queues: Mutex::new(HashMap::new()),
+            queue_size: queue_size.max(1),
+        })
+    }
+
+    /// 获取全局分发器 (首次调用时按配置创建，所有 Bot 共享并发上限)
+    pub fn global(max_concurrency: usize, queue_size: usize) -> Arc<Self> {
+        DISPATCHER
+            .get_or_init(|| Self::new(max_concurrency, queue_size))
+            .clone()
+    }
+
+    /// 将任务投递到指定频道队列，队列已满时返回 false
+    pub fn dispatch(self: &Arc<Self>, key: String, job: Job) -> bool {
+        let mut job = job;
+        loop {
+            let sender = {
+                let mut guard = self.queues.lock().unwrap();
+                guard
+                    .entry(key.clone())
+                    .or_insert_with(|| self.spawn_worker(key.clone()))
+                    .clone()
+            };
+
+            match sender.try_send(job) {
+                Ok(()) => return true,
+                Err(TrySendError::Full(_)) => return false,
+                // 工作协程恰好因空闲退出，移除旧队列后重试
+                Err(TrySendError::Closed(j)) => {
+                    job = j;
+                    let mut guard = self.queues.lock().unwrap();
+                    if guard.get(&key).is_some_and(|s| s.same_channel(&sender)) {
+                        guard.remove(&key);
+                    }
+                }
+            }
+        }
+    }
+
+    fn spawn_worker(self: &Arc<Self>, key: String) -> mpsc::Sender<Job> {
+        let (tx, mut rx) = mpsc::channel::<Job>(self.queue_size);
+        let this = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let job = match tokio::time::timeout(IDLE_TIMEOUT, rx.recv()).await {
+                    Ok(Some(job)) => job,
+                    Ok(None) => break,
+                    Err(_) => {
+                        // 空闲超时：持锁确认队列为空后再注销，避免丢失刚投递的任务
+                        let pending = {
+                            let mut guard = this.queues.lock().unwrap();
+                            match rx.try_recv() {
+                                Ok(job) => Some(job),
+                                Err(_) => {
+                                    guard.remove(&key);
+                                    None
+                                }
+                            }
+                        };
+                        match pending {
+                            Some(job) => job,
+                            None => break,
+                        }
+                    }
+                };
+                this.run_job(&key, job).await;
+            }
+        });
+
+        tx
+    }
+
+    async fn run_job(&self, key: &str, job: Job) {
+        let _permit = match self.semaphore.acquire().await {
+            Ok(p) => p,
+            Err(_) => return,
+        };
+        if AssertUnwindSafe(job).catch_unwind().await.is_err() {
+            error!(target: "Dispatcher", "频道 [{}] 的事件处理发生 Panic", key);
+        }
+    }
+}
+
+/// 计算事件所属频道：群消息按群，其余按用户，系统事件共用一个队列
+pub fn channel_key(bot_id: &str, event: &Event) -> String {
+    let group_id = event
+        .get_i64("group_id")
+        .or_else(|| event.get_u64("group_id").map(|v| v as i64));
+    let user_id = event
+        .get_i64("user_id")
+        .or_else(|| event.get_u64("user_id").map(|v| v as i64));
+
+    match (group_id, user_id) {
+        (Some(gid), _) => format!("{}:group:{}", bot_id, gid),
+        (None, Some(uid)) => format!("{}:user:{}", bot_id, uid),
+        _ => format!("{}:system", bot_id),
+    }
+}
+
+/// 将耗时任务 (如 AI 对话、网页截图) 从频道队列中分离到后台执行，
+/// 使同频道的后续事件无需等待其完成
+pub fn detach<F>(name: &'static str, fut: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        if AssertUnwindSafe(fut).catch_unwind().await.is_err() {
+            error!(target: "Dispatcher", "后台任务 [{}] 发生 Panic", name);
+        }
+    });
+}
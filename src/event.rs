@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use crate::config::AppConfig;
+use crate::db::store::Store;
 use crate::matcher::Matcher;
 use crate::scheduler::Scheduler;
 use sea_orm::DatabaseConnection;
@@ -29,12 +30,11 @@ pub struct BotStatus {
 }
 
 /// 统一的上下文，包含事件数据、可变配置和任务调度器
-/// 注意：event 字段直接持有 EventType，支持在插件链中移交所有权从而实现修改。
-/// Context 实现了 Clone（因为 EventType 包含的 simd_json::OwnedValue 实现了 Clone），
-/// 但在插件流水线中通常通过 Move 传递，避免了 Deep Copy。
+/// 注意：event 字段通过 Arc 共享，克隆 Context 不会深拷贝事件数据。
+/// 插件需要修改事件时使用 Arc::make_mut，仅在事件被共享时才复制一份 (写时复制)。
 #[derive(Clone)]
 pub struct Context {
-    pub event: EventType, // 直接持有，不再使用 Arc
+    pub event: Arc<EventType>,
     pub config: Arc<RwLock<AppConfig>>,
     pub config_save_lock: Arc<AsyncMutex<()>>,
     pub db: DatabaseConnection,
@@ -47,7 +47,7 @@ pub struct Context {
 impl Context {
     /// 尝试将当前事件视为 OneBot 消息事件
     pub fn as_message(&self) -> Option<MessageEvent<'_>> {
-        if let EventType::Onebot(event) = &self.event {
+        if let EventType::Onebot(event) = &*self.event {
             let view = GeneralEventView(event);
             if view.post_type() == Some("message") {
                 return Some(MessageEvent(event));
@@ -58,13 +58,18 @@ impl Context {
 
     /// 获取事件的 Post Type (如果是 OneBot 事件)
     pub fn post_type(&self) -> Option<&str> {
-        if let EventType::Onebot(event) = &self.event {
+        if let EventType::Onebot(event) = &*self.event {
             GeneralEventView(event).post_type()
         } else {
             None
         }
     }
 
+    /// 获取插件的键值存储 (按插件名隔离命名空间)
+    pub fn store(&self, plugin: &str) -> Store {
+        Store::new(self.db.clone(), plugin)
+    }
+
     /// 等待特定条件的用户输入 (交互式操作)
     pub async fn wait_input(
         &self,
@@ -1,42 +1,263 @@
-use chrono::Local;
+use crate::config::LogConfig;
+use chrono::{Local, NaiveDate};
+use std::fs::{self, File, OpenOptions};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Level {
+    Debug,
     Info,
     Warn,
     Error,
-    Debug,
 }
 
+impl Level {
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "debug" | "debg" => Some(Level::Debug),
+            "info" => Some(Level::Info),
+            "warn" | "warning" => Some(Level::Warn),
+            "error" | "erro" => Some(Level::Error),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERRO",
+            Level::Debug => "DEBG",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            Level::Info => "\x1b[32m",  // Green
+            Level::Warn => "\x1b[33m",  // Yellow
+            Level::Error => "\x1b[31m", // Red
+            Level::Debug => "\x1b[34m", // Blue
+        }
+    }
+}
+
+// ================= 日志设置 =================
+
+struct Settings {
+    level: Level,
+    /// 按 target 前缀覆盖的级别，按前缀长度降序排列以便最长匹配
+    targets: Vec<(String, Level)>,
+    json: bool,
+    color: bool,
+    file_dir: Option<PathBuf>,
+    retention_days: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            level: Level::Debug,
+            targets: Vec::new(),
+            json: false,
+            color: std::io::stdout().is_terminal(),
+            file_dir: None,
+            retention_days: 0,
+        }
+    }
+}
+
+impl Settings {
+    fn min_level(&self, target: &str) -> Level {
+        self.targets
+            .iter()
+            .find(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .map(|(_, lv)| *lv)
+            .unwrap_or(self.level)
+    }
+}
+
+/// 按天滚动的日志文件
+struct FileSink {
+    date: NaiveDate,
+    file: File,
+}
+
+static SETTINGS: OnceLock<RwLock<Settings>> = OnceLock::new();
+static FILE_SINK: Mutex<Option<FileSink>> = Mutex::new(None);
+
+fn settings() -> &'static RwLock<Settings> {
+    SETTINGS.get_or_init(|| RwLock::new(Settings::default()))
+}
+
+/// 根据配置初始化日志系统 (在加载配置后调用，之前的日志使用默认设置输出到终端)
+pub fn init(config: &LogConfig) {
+    let level = Level::parse(&config.level).unwrap_or_else(|| {
+        print(
+            Level::Warn,
+            "Log",
+            format_args!("未知的日志级别 '{}'，使用 debug", config.level),
+        );
+        Level::Debug
+    });
+
+    let mut targets: Vec<(String, Level)> = config
+        .targets
+        .iter()
+        .filter_map(|(t, lv)| match Level::parse(lv) {
+            Some(l) => Some((t.clone(), l)),
+            None => {
+                print(
+                    Level::Warn,
+                    "Log",
+                    format_args!("模块 '{}' 的日志级别 '{}' 无效，已忽略", t, lv),
+                );
+                None
+            }
+        })
+        .collect();
+    targets.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+
+    let color = match config.color.as_str() {
+        "always" => true,
+        "never" => false,
+        _ => std::io::stdout().is_terminal(),
+    };
+
+    let file_dir = match (config.file.enabled, config.file.dir.is_empty()) {
+        (false, _) => None,
+        (true, true) => Some(crate::config::data_dir().join("logs")),
+        (true, false) => Some(PathBuf::from(&config.file.dir)),
+    };
+
+    {
+        let mut guard = settings().write().unwrap();
+        *guard = Settings {
+            level,
+            targets,
+            json: config.json,
+            color,
+            file_dir,
+            retention_days: config.file.retention_days,
+        };
+    }
+
+    // 关闭旧的文件句柄，下次写入时按新配置重新打开
+    *FILE_SINK.lock().unwrap() = None;
+}
+
+// ================= 输出 =================
+
 /// 统一日志输出函数
 /// 格式: [Time] [LEVEL] [Target] Message
 pub fn print(level: Level, target: &str, args: std::fmt::Arguments) {
-    let now = Local::now().format("%H:%M:%S");
-
-    // ANSI 颜色代码
-    let gray = "\x1b[90m";
-    let reset = "\x1b[0m";
-    let cyan = "\x1b[36m";
-
-    // Level 颜色与标签
-    let (color, level_str) = match level {
-        Level::Info => ("\x1b[32m", "INFO"),  // Green
-        Level::Warn => ("\x1b[33m", "WARN"),  // Yellow
-        Level::Error => ("\x1b[31m", "ERRO"), // Red
-        Level::Debug => ("\x1b[34m", "DEBG"), // Blue
+    let guard = settings().read().unwrap();
+    if level < guard.min_level(target) {
+        return;
+    }
+
+    let now = Local::now();
+
+    let line = if guard.json {
+        serde_json::json!({
+            "time": now.to_rfc3339(),
+            "level": level.label(),
+            "target": target,
+            "message": args.to_string(),
+        })
+        .to_string()
+    } else {
+        format!(
+            "[{}] [{}] [{}] {}",
+            now.format("%H:%M:%S"),
+            level.label(),
+            target,
+            args
+        )
     };
 
-    println!(
-        "{}[{}] {}[{}] {}{}{}{} {}",
-        gray,
-        now,
-        color,
-        level_str,
-        reset,
-        cyan,
-        format_args!("[{}]", target),
-        reset,
-        args
-    );
+    if guard.color && !guard.json {
+        // ANSI 颜色代码
+        let gray = "\x1b[90m";
+        let reset = "\x1b[0m";
+        let cyan = "\x1b[36m";
+
+        println!(
+            "{}[{}] {}[{}] {}{}[{}]{} {}",
+            gray,
+            now.format("%H:%M:%S"),
+            level.color(),
+            level.label(),
+            reset,
+            cyan,
+            target,
+            reset,
+            args
+        );
+    } else {
+        println!("{}", line);
+    }
+
+    if let Some(dir) = &guard.file_dir {
+        write_file(dir, guard.retention_days, now.date_naive(), &line);
+    }
+}
+
+fn write_file(dir: &Path, retention_days: u32, today: NaiveDate, line: &str) {
+    let mut sink = FILE_SINK.lock().unwrap();
+
+    if sink.as_ref().is_none_or(|s| s.date != today) {
+        *sink = open_file(dir, today);
+        if sink.is_some() {
+            cleanup(dir, retention_days, today);
+        }
+    }
+
+    if let Some(s) = sink.as_mut() {
+        let _ = writeln!(s.file, "{}", line);
+    }
+}
+
+fn open_file(dir: &Path, date: NaiveDate) -> Option<FileSink> {
+    if let Err(e) = fs::create_dir_all(dir) {
+        eprintln!("无法创建日志目录 {:?}: {}", dir, e);
+        return None;
+    }
+    let path = dir.join(format!("ayjx-{}.log", date.format("%Y-%m-%d")));
+    match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(file) => Some(FileSink { date, file }),
+        Err(e) => {
+            eprintln!("无法打开日志文件 {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// 删除超过保留天数的日志文件 (0 表示永久保留)
+fn cleanup(dir: &Path, retention_days: u32, today: NaiveDate) {
+    if retention_days == 0 {
+        return;
+    }
+    let cutoff = today - chrono::Duration::days(retention_days as i64);
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let date = name
+            .strip_prefix("ayjx-")
+            .and_then(|s| s.strip_suffix(".log"))
+            .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok());
+
+        if let Some(d) = date
+            && d < cutoff
+        {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
 }
 
 #[macro_export]
@@ -1,105 +1,73 @@
 mod adapters;
+mod cli;
 mod command;
 mod config;
 mod db;
+mod dispatcher;
 mod event;
 #[macro_use]
 mod log;
 mod matcher;
 mod message;
+mod metrics;
 mod plugins;
 mod scheduler;
 
+use crate::cli::Cli;
 use crate::config::AppConfig;
 use crate::event::{BotStatus, Context, EventType};
 use crate::matcher::Matcher;
 use crate::scheduler::Scheduler;
 use cdp_html_shot::Browser;
-use std::collections::HashSet;
+use clap::Parser;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tokio::fs;
 use tokio::signal;
 use tokio::sync::Mutex as AsyncMutex;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let config_path = "config.toml";
+    let cli = Cli::parse();
+    config::set_data_dir(cli.data_dir.clone());
+    let config_path = cli.config.as_str();
 
-    let db = db::init().await.expect("数据库初始化失败");
+    // 维护子命令：执行后直接退出，不启动 Bot
+    if let Some(command) = cli.command {
+        return cli::run(command, config_path).await;
+    }
 
     // 加载或创建基础配置
-    let mut app_config = if Path::new(config_path).exists() {
-        let content = fs::read_to_string(config_path).await?;
-        match toml::from_str::<AppConfig>(&content) {
-            Ok(cfg) => cfg,
-            Err(e) => {
-                // 如果文件存在但内容为空，使用默认配置
-                if content.trim().is_empty() {
-                    warn!("配置文件为空，将使用默认配置并重新生成。");
-                    AppConfig::default()
-                } else {
-                    // 如果解析失败（如类型错误），直接报错退出，防止覆盖源文件
-                    error!("配置文件 [{}] 解析失败: {}", config_path, e);
-                    error!(
-                        "请检查配置文件格式是否正确（例如字段类型是否匹配）。程序已停止以保护配置不被覆盖。"
-                    );
-                    // 显式指定错误类型，帮助编译器推断 main 函数的返回类型
-                    let err: Box<dyn std::error::Error + Send + Sync> = Box::new(e);
-                    return Err(err);
-                }
-            }
+    let mut app_config = match AppConfig::load(config_path).await {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            // 如果解析失败（如类型错误），直接报错退出，防止覆盖源文件
+            error!("配置文件 [{}] 解析失败: {}", config_path, e);
+            error!(
+                "请检查配置文件格式是否正确（例如字段类型是否匹配）。程序已停止以保护配置不被覆盖。"
+            );
+            return Err(e);
         }
-    } else {
-        // 文件不存在，使用默认配置
-        AppConfig::default()
     };
 
-    // 动态合并插件默认配置
-    let registered_plugins = plugins::get_plugins();
-    let mut config_dirty = false;
-
-    // 清理无效配置：只保留注册过的插件配置
-    let valid_plugin_names: HashSet<&str> = registered_plugins.iter().map(|p| p.name).collect();
-    let unknown_keys: Vec<String> = app_config
-        .plugins
-        .keys()
-        .filter(|k| !valid_plugin_names.contains(k.as_str()))
-        .cloned()
-        .collect();
-
-    for key in unknown_keys {
-        info!("清理无效配置项: [{}]", key);
-        app_config.plugins.remove(&key);
-        config_dirty = true;
-    }
+    // 按配置初始化日志系统
+    log::init(&app_config.log);
+    db::utils::set_week_start(app_config.week_start);
 
-    for plugin in registered_plugins {
-        let default_config = (plugin.default_config)();
-
-        match app_config.plugins.get_mut(plugin.name) {
-            Some(existing_config) => {
-                // 如果配置已存在，尝试合并默认配置中的新字段
-                if let toml::Value::Table(existing_table) = existing_config
-                    && let toml::Value::Table(default_table) = default_config
-                {
-                    for (key, value) in default_table {
-                        if !existing_table.contains_key(&key) {
-                            info!("插件 [{}] 配置补全: 新增字段 '{}'", plugin.name, key);
-                            existing_table.insert(key, value);
-                            config_dirty = true;
-                        }
-                    }
-                }
-            }
-            None => {
-                info!("检测到新插件 [{}]，写入默认配置...", plugin.name);
-                app_config
-                    .plugins
-                    .insert(plugin.name.to_string(), default_config);
-                config_dirty = true;
-            }
-        }
+    // 动态合并插件默认配置 (版本迁移、补全新字段)
+    let config_dirty = plugins::normalize_config(&mut app_config);
+
+    // 逐字段校验插件配置，报告具体的插件与键路径
+    let config_errors = plugins::validate_config(&app_config);
+    for e in &config_errors {
+        error!("配置错误 {}", e);
+    }
+    if !config_errors.is_empty() {
+        warn!(
+            "共发现 {} 处配置错误，相关插件可能无法正常工作。",
+            config_errors.len()
+        );
     }
 
     if config_dirty || !Path::new(config_path).exists() {
@@ -109,6 +77,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         }
     }
 
+    let db = db::init(&app_config.database)
+        .await
+        .expect("数据库初始化失败");
+
     // 初始化全局浏览器实例
     if let Some(ref path) = app_config.browser_path {
         if !path.is_empty() {
@@ -123,6 +95,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let _ = Browser::instance().await;
     }
 
+    // 启动指标导出服务
+    if app_config.metrics.enabled {
+        tokio::spawn(metrics::serve(app_config.metrics.listen.clone()));
+    }
+
     // 构建运行时组件
     let shared_config = Arc::new(RwLock::new(app_config.clone()));
     // 初始化调度器
@@ -130,9 +107,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // 初始化文件写入锁
     let save_lock = Arc::new(AsyncMutex::new(()));
 
+    // 执行数据库迁移 (需在插件 init 之前完成)
+    plugins::run_db_migrations(&db).await?;
+
+    // 定期清理键值存储中已过期的条目
+    {
+        let db = db.clone();
+        scheduler.add_interval(Duration::from_secs(3600), move || {
+            let db = db.clone();
+            async move {
+                if let Err(e) = db::store::purge_expired(&db).await {
+                    warn!(target: "Database", "清理过期键值失败: {}", e);
+                }
+            }
+        });
+    }
+
     // === 触发插件初始化钩子 (生命周期: init) ===
     let init_ctx = Context {
-        event: EventType::Init,
+        event: Arc::new(EventType::Init),
         config: shared_config.clone(),
         config_save_lock: save_lock.clone(),
         db: db.clone(),
@@ -228,26 +221,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     // 执行清理工作
     scheduler.shutdown();
+    // 写入消息记录队列中剩余的数据
+    plugins::recorder::queue::shutdown().await;
     let _ = db.close().await;
 
     // 清理浏览器资源
     cdp_html_shot::Browser::shutdown_global().await;
 
-    // 退出前强制再保存一次配置，确保万无一失
-    let config_snapshot = if let Ok(guard) = shared_config.read() {
-        Some(guard.clone())
-    } else {
-        error!("无法获取配置锁，跳过保存。");
-        None
-    };
-
-    if let Some(cfg) = config_snapshot {
-        if let Err(e) = cfg.save(config_path).await {
-            error!("退出前保存配置失败: {}", e);
-        } else {
-            info!("配置已保存。");
-        }
-    }
+    // 配置在修改时已即时持久化，退出时不再整体回写，以免覆盖运行期间的手动编辑
 
     info!("Bye!");
     Ok(())
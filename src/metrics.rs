@@ -0,0 +1,238 @@
+use axum::Router;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+
+// ================= 指标名称 =================
+
+pub const EVENTS_RECEIVED: &str = "ayjx_events_received_total";
+pub const PLUGIN_LATENCY: &str = "ayjx_plugin_handler_seconds";
+pub const PLUGIN_ERRORS: &str = "ayjx_plugin_errors_total";
+pub const API_LATENCY: &str = "ayjx_api_call_seconds";
+pub const API_TIMEOUTS: &str = "ayjx_api_timeouts_total";
+pub const MESSAGES_SENT: &str = "ayjx_messages_sent_total";
+pub const RECONNECTS: &str = "ayjx_bot_reconnects_total";
+pub const SCHEDULER_RUNS: &str = "ayjx_scheduler_job_runs_total";
+pub const DB_INSERT_LATENCY: &str = "ayjx_recorder_insert_seconds";
+pub const RECORDER_QUEUE_DEPTH: &str = "ayjx_recorder_queue_depth";
+pub const RECORDER_QUEUE_FULL: &str = "ayjx_recorder_queue_full_total";
+pub const RECORDER_ROWS_WRITTEN: &str = "ayjx_recorder_rows_written_total";
+pub const RECORDER_ROWS_DROPPED: &str = "ayjx_recorder_rows_dropped_total";
+
+/// 直方图分桶上界 (秒)
+const BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+fn help(name: &str) -> &'static str {
+    match name {
+        EVENTS_RECEIVED => "Events received from adapters",
+        PLUGIN_LATENCY => "Plugin handler latency",
+        PLUGIN_ERRORS => "Plugin handler errors",
+        API_LATENCY => "OneBot API call latency",
+        API_TIMEOUTS => "OneBot API calls that timed out",
+        MESSAGES_SENT => "Messages sent to bots",
+        RECONNECTS => "Bot reconnect attempts",
+        SCHEDULER_RUNS => "Scheduled job executions",
+        DB_INSERT_LATENCY => "Recorder database batch insert latency",
+        RECORDER_QUEUE_DEPTH => "Messages waiting in the recorder write queue",
+        RECORDER_QUEUE_FULL => "Recorder enqueues that waited on a full queue",
+        RECORDER_ROWS_WRITTEN => "Message records written by the recorder",
+        RECORDER_ROWS_DROPPED => "Message records the recorder failed to write",
+        _ => "",
+    }
+}
+
+// ================= 注册表 =================
+
+type Labels = Vec<(String, String)>;
+
+struct Histogram {
+    counts: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+struct Registry {
+    counters: BTreeMap<&'static str, BTreeMap<Labels, u64>>,
+    gauges: BTreeMap<&'static str, BTreeMap<Labels, f64>>,
+    histograms: BTreeMap<&'static str, BTreeMap<Labels, Histogram>>,
+}
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+fn registry() -> &'static Mutex<Registry> {
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+fn to_labels(labels: &[(&str, &str)]) -> Labels {
+    labels
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// 计数器 +1
+pub fn inc_counter(name: &'static str, labels: &[(&str, &str)]) {
+    let mut reg = registry().lock().unwrap();
+    *reg.counters
+        .entry(name)
+        .or_default()
+        .entry(to_labels(labels))
+        .or_insert(0) += 1;
+}
+
+/// 计数器增加指定值
+pub fn add_counter(name: &'static str, labels: &[(&str, &str)], value: u64) {
+    let mut reg = registry().lock().unwrap();
+    *reg.counters
+        .entry(name)
+        .or_default()
+        .entry(to_labels(labels))
+        .or_insert(0) += value;
+}
+
+/// 设置仪表盘当前值
+pub fn set_gauge(name: &'static str, labels: &[(&str, &str)], value: f64) {
+    let mut reg = registry().lock().unwrap();
+    reg.gauges
+        .entry(name)
+        .or_default()
+        .insert(to_labels(labels), value);
+}
+
+/// 记录一次耗时 (秒)
+pub fn observe(name: &'static str, labels: &[(&str, &str)], seconds: f64) {
+    let mut reg = registry().lock().unwrap();
+    let hist = reg
+        .histograms
+        .entry(name)
+        .or_default()
+        .entry(to_labels(labels))
+        .or_insert(Histogram {
+            counts: [0; BUCKETS.len()],
+            sum: 0.0,
+            count: 0,
+        });
+
+    for (i, bound) in BUCKETS.iter().enumerate() {
+        if seconds <= *bound {
+            hist.counts[i] += 1;
+        }
+    }
+    hist.sum += seconds;
+    hist.count += 1;
+}
+
+// ================= 导出 =================
+
+fn escape(v: &str) -> String {
+    v.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_labels(labels: &Labels, extra: Option<(&str, &str)>) -> String {
+    let mut parts: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+        .collect();
+    if let Some((k, v)) = extra {
+        parts.push(format!("{}=\"{}\"", k, v));
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", parts.join(","))
+    }
+}
+
+/// 以 Prometheus 文本格式导出全部指标
+pub fn render() -> String {
+    let reg = registry().lock().unwrap();
+    let mut out = String::new();
+
+    for (name, series) in &reg.counters {
+        let _ = writeln!(out, "# HELP {} {}", name, help(name));
+        let _ = writeln!(out, "# TYPE {} counter", name);
+        for (labels, value) in series {
+            let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+        }
+    }
+
+    for (name, series) in &reg.gauges {
+        let _ = writeln!(out, "# HELP {} {}", name, help(name));
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (labels, value) in series {
+            let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+        }
+    }
+
+    for (name, series) in &reg.histograms {
+        let _ = writeln!(out, "# HELP {} {}", name, help(name));
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (labels, hist) in series {
+            for (i, bound) in BUCKETS.iter().enumerate() {
+                let le = bound.to_string();
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{} {}",
+                    name,
+                    format_labels(labels, Some(("le", &le))),
+                    hist.counts[i]
+                );
+            }
+            let _ = writeln!(
+                out,
+                "{}_bucket{} {}",
+                name,
+                format_labels(labels, Some(("le", "+Inf"))),
+                hist.count
+            );
+            let _ = writeln!(
+                out,
+                "{}_sum{} {}",
+                name,
+                format_labels(labels, None),
+                hist.sum
+            );
+            let _ = writeln!(
+                out,
+                "{}_count{} {}",
+                name,
+                format_labels(labels, None),
+                hist.count
+            );
+        }
+    }
+
+    out
+}
+
+async fn handle_metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render(),
+    )
+}
+
+/// 启动 /metrics 导出服务
+pub async fn serve(listen: String) {
+    let listener = match tokio::net::TcpListener::bind(&listen).await {
+        Ok(l) => l,
+        Err(e) => {
+            error!(target: "Metrics", "监听 {} 失败: {}", listen, e);
+            return;
+        }
+    };
+    info!(target: "Metrics", "指标导出已启动: http://{}/metrics", listen);
+
+    let app = Router::new().route("/metrics", get(handle_metrics));
+    if let Err(e) = axum::serve(listener, app).await {
+        error!(target: "Metrics", "指标服务异常退出: {}", e);
+    }
+}
@@ -1,12 +1,23 @@
 #![allow(dead_code)]
 
-use crate::adapters::onebot::{LockedWriter, send_frame_raw};
+use crate::adapters::onebot::{LockedWriter, send_frame_raw, send_msg};
+use crate::config::{AppConfig, data_dir};
+use crate::db::migrations::{self, Migration};
+use crate::db::store;
 use crate::event::{BotStatus, Context, Event, EventType};
 use crate::matcher::Matcher;
+use crate::message::Message;
+use crate::metrics;
+use futures_util::FutureExt;
 use futures_util::future::BoxFuture;
+use sea_orm::DatabaseConnection;
 use serde::{Serialize, de::DeserializeOwned};
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
 use std::path::PathBuf;
-use std::sync::{Arc, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use tokio::fs;
 use toml::Value;
 
@@ -17,13 +28,53 @@ pub type PluginHandler =
 
 pub type PluginInitHandler = fn(Context) -> BoxFuture<'static, Result<(), PluginError>>;
 
+/// 配置迁移函数：原地修改插件配置表，将其从某一版本升级到下一版本
+pub type ConfigMigration = fn(&mut toml::Table);
+
+/// 配置校验函数：以插件实际的配置类型反序列化配置表，失败时返回 (键路径, 原因)，根路径为空
+pub type ConfigValidator = fn(&Value) -> Result<(), (String, String)>;
+
+/// 配置表中记录版本号的键
+pub const CONFIG_VERSION_KEY: &str = "config_version";
+
 pub struct Plugin {
     pub name: &'static str,
     pub handler: PluginHandler,
     pub on_init: Option<PluginInitHandler>,
     /// 当 Bot 连接成功且获取到自身信息后触发 (用于注册主动推送任务等)
     pub on_connected: Option<PluginHandler>,
+    /// 当已获取登录信息的 Bot 连接断开后触发 (用于清理 on_connected 中登记的状态)
+    pub on_disconnected: Option<PluginInitHandler>,
+    /// 配置热重载后触发 (仅当该插件的配置表发生变化时)，用于刷新初始化时缓存的状态
+    pub on_config_changed: Option<PluginInitHandler>,
     pub default_config: fn() -> Value,
+    pub validate_config: ConfigValidator,
+    /// 配置迁移列表：migrations[i] 将配置从 v(i+1) 升级到 v(i+2)
+    /// 字段改名或移动时在末尾追加一项，已发布的迁移不可修改
+    pub migrations: &'static [ConfigMigration],
+    /// 数据库迁移 (按版本递增)，在 init 之前执行，由 schema_migrations 表记录已应用的版本
+    pub db_migrations: &'static [Migration],
+}
+
+impl Plugin {
+    /// 当前配置版本 (未定义迁移的插件为 v1)
+    pub fn config_version(&self) -> u32 {
+        self.migrations.len() as u32 + 1
+    }
+
+    /// 带版本号的默认配置
+    fn versioned_default_config(&self) -> Value {
+        let mut value = (self.default_config)();
+        if let Value::Table(table) = &mut value
+            && !self.migrations.is_empty()
+        {
+            table.insert(
+                CONFIG_VERSION_KEY.to_string(),
+                Value::Integer(self.config_version() as i64),
+            );
+        }
+        value
+    }
 }
 
 static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
@@ -51,7 +102,12 @@ macro_rules! register_plugins {
                                 handler: $module::handle,
                                 on_init: None,
                                 on_connected: None,
+                                on_disconnected: None,
+                                on_config_changed: None,
                                 default_config: $module::default_config,
+                                validate_config: $module::validate_config,
+                                migrations: &[],
+                                db_migrations: &[],
                             };
                             // 应用自定义覆盖 (如果有)
                             $(
@@ -73,6 +129,46 @@ pub fn register_plugins() -> &'static [Plugin] {
     get_plugins()
 }
 
+/// 执行所有插件的数据库迁移 (不论插件是否启用，保证启用时表结构已就绪)
+pub async fn run_db_migrations(db: &DatabaseConnection) -> Result<usize, PluginError> {
+    // 框架自身的表 (键值存储) 先于插件迁移
+    let sources: Vec<(&str, &'static [Migration])> = std::iter::once(("store", store::MIGRATIONS))
+        .chain(
+            get_plugins()
+                .iter()
+                .filter(|p| !p.db_migrations.is_empty())
+                .map(|p| (p.name, p.db_migrations)),
+        )
+        .collect();
+    Ok(migrations::run(db, &sources).await?)
+}
+
+/// 已成功初始化的插件 (热重载启用插件时据此判断是否需要执行 on_init)
+static INITIALIZED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+fn initialized() -> &'static Mutex<HashSet<&'static str>> {
+    INITIALIZED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// 执行单个插件的初始化逻辑，成功后记录到已初始化列表
+async fn init_plugin(ctx: &Context, plugin: &Plugin) {
+    if let Some(init_fn) = plugin.on_init {
+        // 执行初始化
+        match init_fn(system_context(ctx)).await {
+            Ok(_) => {
+                info!(target: "Plugin", "✅ [{}] 就绪 (Init Success)", plugin.name);
+            }
+            Err(e) => {
+                error!(target: "Plugin", "❌ [{}] 初始化失败: {}", plugin.name, e);
+                return;
+            }
+        }
+    } else {
+        info!(target: "Plugin", "✅ [{}] 就绪", plugin.name);
+    }
+    initialized().lock().unwrap().insert(plugin.name);
+}
+
 /// 执行所有插件的初始化逻辑
 pub async fn do_init(ctx: Context) -> Result<(), PluginError> {
     let plugins = get_plugins();
@@ -114,38 +210,29 @@ pub async fn do_init(ctx: Context) -> Result<(), PluginError> {
             continue;
         }
 
-        if let Some(init_fn) = plugin.on_init {
-            let init_ctx = Context {
-                event: EventType::Init, // 移除 Arc
-                config: ctx.config.clone(),
-                config_save_lock: ctx.config_save_lock.clone(),
-                db: ctx.db.clone(),
-                scheduler: ctx.scheduler.clone(),
-                matcher: Arc::new(Matcher::new()),
-                config_path: ctx.config_path.clone(),
-                bot: BotStatus {
-                    adapter: "system".to_string(),
-                    platform: "internal".to_string(),
-                    login_user: Default::default(),
-                },
-            };
-
-            // 执行初始化
-            match init_fn(init_ctx).await {
-                Ok(_) => {
-                    info!(target: "Plugin", "✅ [{}] 就绪 (Init Success)", plugin.name);
-                }
-                Err(e) => {
-                    error!(target: "Plugin", "❌ [{}] 初始化失败: {}", plugin.name, e);
-                }
-            }
-        } else {
-            info!(target: "Plugin", "✅ [{}] 就绪", plugin.name);
-        }
+        init_plugin(&ctx, plugin).await;
     }
     Ok(())
 }
 
+/// 构造不绑定任何 Bot 的系统上下文 (用于 init / 配置变更等生命周期钩子)
+fn system_context(ctx: &Context) -> Context {
+    Context {
+        event: Arc::new(EventType::Init),
+        config: ctx.config.clone(),
+        config_save_lock: ctx.config_save_lock.clone(),
+        db: ctx.db.clone(),
+        scheduler: ctx.scheduler.clone(),
+        matcher: Arc::new(Matcher::new()),
+        config_path: ctx.config_path.clone(),
+        bot: BotStatus {
+            adapter: "system".to_string(),
+            platform: "internal".to_string(),
+            login_user: Default::default(),
+        },
+    }
+}
+
 /// 当 Bot 连接建立后触发（用于注册定时任务或主动操作）
 pub async fn do_connected(ctx: Context, writer: LockedWriter) -> Result<(), PluginError> {
     let plugins = get_plugins();
@@ -176,6 +263,20 @@ pub async fn do_connected(ctx: Context, writer: LockedWriter) -> Result<(), Plug
     Ok(())
 }
 
+/// 当 Bot 连接断开后触发
+pub async fn do_disconnected(ctx: Context) {
+    for plugin in get_plugins() {
+        if !is_enabled(&ctx, plugin.name) {
+            continue;
+        }
+        if let Some(hook) = plugin.on_disconnected
+            && let Err(e) = hook(ctx.clone()).await
+        {
+            error!(target: "Plugin", "❌ [{}] 断开钩子执行失败: {}", plugin.name, e);
+        }
+    }
+}
+
 /// 运行插件流水线
 pub async fn run(mut ctx: Context, writer: LockedWriter) -> Result<(), PluginError> {
     let plugins = get_plugins();
@@ -196,22 +297,71 @@ pub async fn run(mut ctx: Context, writer: LockedWriter) -> Result<(), PluginErr
             continue;
         }
 
+        if breaker_is_open(plugin.name) {
+            continue;
+        }
+
         // ctx 在这里 Move 进 handler，若插件返回 Some(ctx) 则接力给下一个插件
-        // 这样插件拥有 Context 的所有权，可以修改 Context.event 中的内容
-        match (plugin.handler)(ctx, writer.clone()).await? {
-            Some(next_ctx) => {
+        // 插件可通过 Arc::make_mut 修改 Context.event 中的内容 (写时复制)
+        // 保留一份副本，插件出错/超时/Panic 时使用原始上下文继续执行后续插件
+        // event 通过 Arc 共享，这里的克隆只增加引用计数，不会深拷贝事件
+        let backup = ctx.clone();
+        let timeout = handler_timeout(&ctx, plugin.name);
+
+        let started = Instant::now();
+        let fut = AssertUnwindSafe((plugin.handler)(ctx, writer.clone())).catch_unwind();
+        let outcome = tokio::time::timeout(timeout, fut).await;
+        metrics::observe(
+            metrics::PLUGIN_LATENCY,
+            &[("plugin", plugin.name)],
+            started.elapsed().as_secs_f64(),
+        );
+
+        let failure = match outcome {
+            Ok(Ok(Ok(Some(next_ctx)))) => {
                 ctx = next_ctx;
+                None
+            }
+            Ok(Ok(Ok(None))) => {
+                record_success(plugin.name);
+                return Ok(());
+            }
+            Ok(Ok(Err(e))) => {
+                ctx = backup;
+                Some(format!("处理失败: {}", e))
+            }
+            Ok(Err(panic)) => {
+                ctx = backup;
+                Some(format!("发生 Panic: {}", panic_message(panic.as_ref())))
+            }
+            Err(_) => {
+                ctx = backup;
+                Some(format!("处理超时 (>{}s)", timeout.as_secs()))
+            }
+        };
+
+        match failure {
+            None => record_success(plugin.name),
+            Some(reason) => {
+                metrics::inc_counter(metrics::PLUGIN_ERRORS, &[("plugin", plugin.name)]);
+                error!(target: "Plugin", "❌ [{}] {}", plugin.name, reason);
+                record_failure(&ctx, &writer, plugin.name, &reason);
             }
-            None => return Ok(()),
         }
     }
 
-    // 注意：ctx.event 现在是 EventType，可以直接 match 引用
-    match &ctx.event {
+    match &*ctx.event {
         EventType::Onebot(_) => {}
         EventType::BeforeSend(packet) => {
             let json_str = simd_json::to_string(&packet)?;
             send_frame_raw(writer, json_str).await?;
+            metrics::inc_counter(
+                metrics::MESSAGES_SENT,
+                &[
+                    ("action", packet.action.as_str()),
+                    ("bot", ctx.bot.login_user.id.as_str()),
+                ],
+            );
         }
         EventType::Init => {}
     }
@@ -219,6 +369,319 @@ pub async fn run(mut ctx: Context, writer: LockedWriter) -> Result<(), PluginErr
     Ok(())
 }
 
+// ================= 配置管理 =================
+
+/// 整理插件配置：执行版本迁移，补全新插件及新增字段的默认值
+/// 未注册插件的配置表保留在文件中 (可能来自暂时移除的插件)，仅给出警告
+/// 返回配置是否被修改
+pub fn normalize_config(cfg: &mut AppConfig) -> bool {
+    let plugins = get_plugins();
+    let mut dirty = false;
+
+    for key in cfg.plugins.keys() {
+        if !plugins.iter().any(|p| p.name == key.as_str()) {
+            warn!("配置项 [{}] 不属于任何已注册插件，已忽略", key);
+        }
+    }
+
+    for plugin in plugins {
+        let default_config = plugin.versioned_default_config();
+
+        match cfg.plugins.get_mut(plugin.name) {
+            Some(existing_config) => {
+                if let Value::Table(existing_table) = existing_config
+                    && migrate_plugin_config(plugin, existing_table)
+                {
+                    dirty = true;
+                }
+
+                // 如果配置已存在，尝试合并默认配置中的新字段
+                if let Value::Table(existing_table) = existing_config
+                    && let Value::Table(default_table) = default_config
+                {
+                    for (key, value) in default_table {
+                        if !existing_table.contains_key(&key) {
+                            info!("插件 [{}] 配置补全: 新增字段 '{}'", plugin.name, key);
+                            existing_table.insert(key, value);
+                            dirty = true;
+                        }
+                    }
+                }
+            }
+            None => {
+                info!("检测到新插件 [{}]，写入默认配置...", plugin.name);
+                cfg.plugins.insert(plugin.name.to_string(), default_config);
+                dirty = true;
+            }
+        }
+    }
+
+    dirty
+}
+
+/// 依次执行尚未应用的迁移，返回是否发生迁移
+fn migrate_plugin_config(plugin: &Plugin, table: &mut toml::Table) -> bool {
+    let current = plugin.config_version();
+    let version = table
+        .get(CONFIG_VERSION_KEY)
+        .and_then(|v| v.as_integer())
+        .unwrap_or(1)
+        .max(1) as u32;
+
+    if version > current {
+        warn!(
+            "插件 [{}] 的配置版本 v{} 高于程序支持的 v{}，可能来自更新的版本",
+            plugin.name, version, current
+        );
+        return false;
+    }
+    if version == current {
+        return false;
+    }
+
+    for migration in &plugin.migrations[(version - 1) as usize..] {
+        migration(table);
+    }
+    table.insert(
+        CONFIG_VERSION_KEY.to_string(),
+        Value::Integer(current as i64),
+    );
+    info!(
+        "插件 [{}] 配置已从 v{} 迁移至 v{}",
+        plugin.name, version, current
+    );
+    true
+}
+
+/// 以各插件的配置类型反序列化配置表进行校验，返回全部错误 (形如 "插件名.键路径: 原因")
+pub fn validate_config(cfg: &AppConfig) -> Vec<String> {
+    let mut errors = Vec::new();
+    for plugin in get_plugins() {
+        match cfg.plugins.get(plugin.name) {
+            Some(value @ Value::Table(_)) => {
+                if let Err((path, reason)) = (plugin.validate_config)(value) {
+                    let key = if path.is_empty() {
+                        plugin.name.to_string()
+                    } else {
+                        format!("{}.{}", plugin.name, path)
+                    };
+                    errors.push(format!("{}: {}", key, reason));
+                }
+            }
+            Some(_) => errors.push(format!("{}: 插件配置必须是表", plugin.name)),
+            None => {}
+        }
+    }
+    errors
+}
+
+/// 以类型 T 反序列化配置表，返回出错字段的路径与原因 (供各插件的 validate_config 使用)
+pub fn validate_as<T: DeserializeOwned>(value: &Value) -> Result<(), (String, String)> {
+    match serde_path_to_error::deserialize::<_, T>(value.clone()) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            // 根路径显示为 "."
+            let path = e.path().to_string();
+            let path = if path == "." { String::new() } else { path };
+            Err((path, e.into_inner().to_string()))
+        }
+    }
+}
+
+/// 从磁盘重新加载配置
+/// 校验通过后整体替换共享配置，并对配置发生变化的已启用插件触发 on_config_changed；
+/// force 为 true 时 (管理员手动重载) 对所有已启用插件触发，以便插件重新读取自己的数据文件。
+/// 由禁用变为启用且从未初始化过的插件会先执行 on_init。
+/// 校验失败时返回具体错误，旧配置保持生效。
+/// 注意：Bot 连接参数、监听地址等仅在启动时读取的配置需要重启后生效。
+pub async fn reload_config(ctx: &Context, force: bool) -> Result<Vec<&'static str>, PluginError> {
+    let _fs_guard = ctx.config_save_lock.lock().await;
+
+    let content = fs::read_to_string(&ctx.config_path).await?;
+    let mut new_cfg = AppConfig::parse(&content).map_err(|e| format!("配置文件解析失败: {}", e))?;
+
+    normalize_config(&mut new_cfg);
+
+    let errors = validate_config(&new_cfg);
+    if !errors.is_empty() {
+        return Err(format!("配置校验失败:\n{}", errors.join("\n")).into());
+    }
+
+    let changed: Vec<&'static str> = {
+        let mut guard = ctx.config.write().unwrap();
+        let changed = get_plugins()
+            .iter()
+            .filter(|p| guard.plugins.get(p.name) != new_cfg.plugins.get(p.name))
+            .map(|p| p.name)
+            .collect();
+        crate::log::init(&new_cfg.log);
+        crate::db::utils::set_week_start(new_cfg.week_start);
+        *guard = new_cfg;
+        changed
+    };
+
+    drop(_fs_guard);
+
+    for plugin in get_plugins() {
+        if !(force || changed.contains(&plugin.name)) || !is_enabled(ctx, plugin.name) {
+            continue;
+        }
+
+        // 新启用的插件：on_init 会读取最新配置，无需再触发 on_config_changed
+        let needs_init = !initialized().lock().unwrap().contains(plugin.name);
+        if needs_init {
+            init_plugin(ctx, plugin).await;
+            if plugin.on_connected.is_some() {
+                warn!(target: "Plugin", "[{}] 已启用，其连接钩子将在 Bot 下次重连时触发", plugin.name);
+            }
+            continue;
+        }
+
+        if let Some(hook) = plugin.on_config_changed
+            && let Err(e) = hook(system_context(ctx)).await
+        {
+            error!(target: "Plugin", "❌ [{}] 配置变更钩子执行失败: {}", plugin.name, e);
+        }
+    }
+
+    Ok(changed)
+}
+
+fn is_enabled(ctx: &Context, plugin_name: &str) -> bool {
+    let guard = ctx.config.read().unwrap();
+    guard
+        .plugins
+        .get(plugin_name)
+        .and_then(|v| v.get("enabled"))
+        .and_then(|x| x.as_bool())
+        .unwrap_or(false)
+}
+
+// ================= 故障隔离 =================
+
+/// 熔断器状态
+#[derive(Default)]
+struct BreakerState {
+    /// 连续失败次数
+    failures: u32,
+    /// 熔断截止时间 (None 表示未熔断)
+    open_until: Option<Instant>,
+}
+
+static BREAKERS: OnceLock<Mutex<HashMap<&'static str, BreakerState>>> = OnceLock::new();
+
+fn breakers() -> &'static Mutex<HashMap<&'static str, BreakerState>> {
+    BREAKERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 插件是否处于熔断状态 (冷却期结束后自动半开，允许再次尝试)
+fn breaker_is_open(name: &'static str) -> bool {
+    let mut guard = breakers().lock().unwrap();
+    match guard.get_mut(name) {
+        Some(state) => match state.open_until {
+            Some(until) if Instant::now() < until => true,
+            Some(_) => {
+                state.open_until = None;
+                info!(target: "Plugin", "🔄 [{}] 熔断冷却结束，尝试恢复", name);
+                false
+            }
+            None => false,
+        },
+        None => false,
+    }
+}
+
+fn record_success(name: &'static str) {
+    let mut guard = breakers().lock().unwrap();
+    if let Some(state) = guard.get_mut(name) {
+        state.failures = 0;
+    }
+}
+
+/// 记录一次失败，达到阈值时熔断插件并通知超级管理员
+fn record_failure(ctx: &Context, writer: &LockedWriter, name: &'static str, reason: &str) {
+    let (threshold, cooldown, superusers) = {
+        let guard = ctx.config.read().unwrap();
+        (
+            guard.pipeline.breaker_threshold,
+            guard.pipeline.breaker_cooldown_seconds,
+            guard.superusers.clone(),
+        )
+    };
+
+    if threshold == 0 {
+        return;
+    }
+
+    let tripped = {
+        let mut guard = breakers().lock().unwrap();
+        let state = guard.entry(name).or_default();
+        state.failures += 1;
+        if state.failures >= threshold {
+            // 保留 threshold - 1 次计数，半开状态下再次失败会立即重新熔断
+            state.failures = threshold - 1;
+            state.open_until = Some(Instant::now() + Duration::from_secs(cooldown));
+            true
+        } else {
+            false
+        }
+    };
+
+    if !tripped {
+        return;
+    }
+
+    warn!(
+        target: "Plugin",
+        "⛔ [{}] 连续失败 {} 次，已暂停 {} 秒",
+        name, threshold, cooldown
+    );
+
+    if superusers.is_empty() || ctx.bot.adapter == "system" {
+        return;
+    }
+
+    let text = format!(
+        "⛔ 插件 [{}] 连续失败 {} 次，已自动停用 {} 秒。\n最后一次错误: {}",
+        name, threshold, cooldown, reason
+    );
+    let ctx = ctx.clone();
+    let writer = writer.clone();
+
+    // 通知需经过发送流水线，此处装箱以打断 run 的递归类型
+    let notify: BoxFuture<'static, ()> = Box::pin(async move {
+        for uid in superusers {
+            let msg = Message::new().text(text.clone());
+            if let Err(e) = send_msg(&ctx, writer.clone(), None, Some(uid), msg).await {
+                warn!(target: "Plugin", "熔断通知发送失败 ({}): {}", uid, e);
+            }
+        }
+    });
+    tokio::spawn(notify);
+}
+
+/// 获取插件处理超时时间 (优先使用插件级覆盖)
+fn handler_timeout(ctx: &Context, name: &str) -> Duration {
+    let guard = ctx.config.read().unwrap();
+    let secs = guard
+        .pipeline
+        .timeouts
+        .get(name)
+        .copied()
+        .unwrap_or(guard.pipeline.handler_timeout_seconds);
+    Duration::from_secs(secs)
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "未知错误".to_string()
+    }
+}
+
 // ================= 工具函数 =================
 
 /// 将伪造/修改过的事件推送回流水线
@@ -228,7 +691,7 @@ pub async fn send_fake_event(
     event: Event,
 ) -> Result<(), PluginError> {
     let new_ctx = Context {
-        event: EventType::Onebot(event),
+        event: Arc::new(EventType::Onebot(event)),
         config: ctx.config.clone(),
         config_save_lock: ctx.config_save_lock.clone(),
         db: ctx.db.clone(),
@@ -240,28 +703,78 @@ pub async fn send_fake_event(
     run(new_ctx, writer).await
 }
 
+/// 插件数据目录 (<数据目录>/<插件名>)
 pub async fn get_data_dir(plugin_name: &str) -> Result<PathBuf, PluginError> {
-    let mut path = std::env::current_exe()?
-        .parent()
-        .ok_or("Cannot get parent dir")?
-        .to_path_buf();
-    path.push("data");
-    path.push(plugin_name);
+    let path = data_dir().join(plugin_name);
     if !path.exists() {
-        fs::create_dir_all(&path).await?;
+        if let Some(legacy) = legacy_data_dir(plugin_name) {
+            // 旧版本的插件数据位于可执行文件所在目录下的 data，首次访问时迁移过来
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            match fs::rename(&legacy, &path).await {
+                Ok(()) => {
+                    info!(target: "Plugin", "已将插件 [{}] 的数据从 {} 迁移至 {}", plugin_name, legacy.display(), path.display());
+                }
+                Err(e) => {
+                    warn!(
+                        target: "Plugin",
+                        "插件 [{}] 的数据迁移失败，继续使用旧目录 {}: {} (可手动移动，或通过 --data-dir 指定)",
+                        plugin_name,
+                        legacy.display(),
+                        e
+                    );
+                    return Ok(legacy);
+                }
+            }
+        } else {
+            fs::create_dir_all(&path).await?;
+        }
     }
     Ok(path)
 }
 
+/// 旧版本的插件数据目录 (<可执行文件目录>/data/<插件名>)，存在且与当前目录不同时返回
+fn legacy_data_dir(plugin_name: &str) -> Option<PathBuf> {
+    let legacy = std::env::current_exe()
+        .ok()?
+        .parent()?
+        .join("data")
+        .join(plugin_name);
+    if !legacy.is_dir() {
+        return None;
+    }
+    let current = std::path::absolute(data_dir().join(plugin_name)).ok()?;
+    let legacy_abs = std::path::absolute(&legacy).ok()?;
+    (current != legacy_abs).then_some(legacy)
+}
+
+/// 读取插件配置，反序列化失败时给出警告 (同一错误只提示一次) 并返回 None
 pub fn get_config<T>(ctx: &Context, plugin_name: &str) -> Option<T>
 where
     T: DeserializeOwned,
 {
-    let guard = ctx.config.read().unwrap();
-    guard
-        .plugins
-        .get(plugin_name)
-        .and_then(|v| T::deserialize(v.clone()).ok())
+    let value = {
+        let guard = ctx.config.read().unwrap();
+        guard.plugins.get(plugin_name)?.clone()
+    };
+
+    match T::deserialize(value) {
+        Ok(cfg) => Some(cfg),
+        Err(e) => {
+            static REPORTED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+            let key = format!("{}: {}", plugin_name, e);
+            let first = REPORTED
+                .get_or_init(|| Mutex::new(HashSet::new()))
+                .lock()
+                .unwrap()
+                .insert(key);
+            if first {
+                warn!(target: "Plugin", "[{}] 配置无效: {}", plugin_name, e);
+            }
+            None
+        }
+    }
 }
 
 /// 修改配置 (异步 & 自动持久化 & 线程安全)
@@ -270,26 +783,53 @@ where
     T: Serialize + DeserializeOwned + Clone,
     F: FnOnce(T) -> T,
 {
+    // 先持有写入锁再修改内存，避免与热重载交错导致修改被旧文件覆盖
+    let _fs_guard = ctx.config_save_lock.lock().await;
+
     {
         let mut guard = ctx.config.write().unwrap();
-        if let Some(v) = guard.plugins.get_mut(plugin_name)
-            && let Ok(current_cfg) = T::deserialize(v.clone())
+        if let Some(Value::Table(table)) = guard.plugins.get_mut(plugin_name)
+            && let Ok(current_cfg) = T::deserialize(Value::Table(table.clone()))
         {
-            let new_cfg = f(current_cfg);
-            if let Ok(new_val) = Value::try_from(new_cfg) {
-                *v = new_val;
+            // 合并到原配置表，保留 config_version 与配置类型之外的键
+            let old_val = Value::try_from(current_cfg.clone());
+            let new_val = Value::try_from(f(current_cfg));
+            if let (Ok(Value::Table(old)), Ok(Value::Table(new))) = (old_val, new_val) {
+                // 修改后不再序列化的字段 (如被设为 None 的 Option) 需要从原表中移除
+                for key in old.keys() {
+                    if !new.contains_key(key) {
+                        table.remove(key);
+                    }
+                }
+                table.extend(new);
             }
         }
     }
 
-    let _fs_guard = ctx.config_save_lock.lock().await;
-
     let latest_config_snapshot = {
         let guard = ctx.config.read().unwrap();
         guard.clone()
     };
 
-    latest_config_snapshot.save(&ctx.config_path).await?;
+    latest_config_snapshot
+        .save_plugin(&ctx.config_path, plugin_name)
+        .await?;
 
     Ok(())
 }
+
+/// 转义 HTML 特殊字符 (用于插件生成的 HTML 页面)
+pub fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,229 @@
+use crate::adapters::onebot::{LockedWriter, send_msg};
+use crate::command::get_prefixes;
+use crate::config::build_config;
+use crate::db::utils::parse_time_range;
+use crate::dispatcher::detach;
+use crate::event::Context;
+use crate::message::Message;
+use crate::plugins::{PluginError, get_config, validate_as};
+use cdp_html_shot::{Browser, CaptureOptions, Viewport};
+use chrono::{Datelike, Local};
+use futures_util::future::BoxFuture;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use simd_json::base::ValueAsScalar;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::time;
+use toml::Value;
+
+mod data;
+mod template;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Config {
+    enabled: bool,
+    /// 年度热词数量
+    #[serde(default = "default_top_words")]
+    top_words: usize,
+    /// 渲染宽度 (像素)
+    #[serde(default = "default_width")]
+    width: u32,
+    /// 截图最大高度 (像素)
+    #[serde(default = "default_max_height")]
+    max_height: u32,
+}
+
+fn default_top_words() -> usize {
+    15
+}
+
+fn default_width() -> u32 {
+    720
+}
+
+fn default_max_height() -> u32 {
+    6000
+}
+
+pub fn default_config() -> Value {
+    build_config(Config {
+        enabled: true,
+        top_words: default_top_words(),
+        width: default_width(),
+        max_height: default_max_height(),
+    })
+}
+
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
+static COMMAND_REGEX: OnceLock<Regex> = OnceLock::new();
+
+fn get_regex() -> &'static Regex {
+    COMMAND_REGEX.get_or_init(|| Regex::new(r"^(我的|本群)(今年|去年|\d{4}年?)?年度报告$").unwrap())
+}
+
+pub fn handle(
+    ctx: Context,
+    writer: LockedWriter,
+) -> BoxFuture<'static, Result<Option<Context>, PluginError>> {
+    Box::pin(async move {
+        let msg = match ctx.as_message() {
+            Some(m) => m,
+            None => return Ok(Some(ctx)),
+        };
+        let text = msg.text();
+        let trimmed_text = text.trim();
+
+        let prefixes = get_prefixes(&ctx);
+        let content = if prefixes.is_empty() {
+            Some(trimmed_text)
+        } else {
+            prefixes
+                .iter()
+                .find(|p| trimmed_text.starts_with(p.as_str()))
+                .map(|p| trimmed_text[p.len()..].trim_start())
+        };
+
+        let Some(caps) = content.and_then(|c| get_regex().captures(c)) else {
+            return Ok(Some(ctx));
+        };
+
+        let scope = caps.get(1).map_or("", |m| m.as_str());
+        let year_str = caps.get(2).map_or("今年", |m| m.as_str());
+
+        let group_id = msg.group_id();
+        let user_id = msg.user_id();
+        let message_id = msg.message_id();
+
+        if scope == "本群" && group_id.is_none() {
+            let reply = Message::new().text("请在群聊中使用“本群年度报告”，或使用“我的年度报告”。");
+            send_msg(&ctx, writer, group_id, Some(user_id), reply).await?;
+            return Ok(None);
+        }
+
+        // "2024" 与 "2024年" 统一交给时间解析
+        let time_expr = if year_str.ends_with('年') {
+            year_str.to_string()
+        } else {
+            format!("{}年", year_str)
+        };
+        let Some((start, end)) = parse_time_range(&time_expr) else {
+            let reply = Message::new().text(format!("无法识别的时间: {}", year_str));
+            send_msg(&ctx, writer, group_id, Some(user_id), reply).await?;
+            return Ok(None);
+        };
+
+        let year = match year_str {
+            "今年" => Local::now().year(),
+            "去年" => Local::now().year() - 1,
+            s => s.trim_end_matches('年').parse().unwrap_or_default(),
+        };
+        let (query_user, title) = match scope {
+            "我的" => (Some(user_id), format!("{} 我的年度报告", year)),
+            _ => (None, format!("{} 本群年度报告", year)),
+        };
+        let subtitle = if scope == "我的" {
+            let name = msg.sender_name();
+            match group_id {
+                Some(_) => format!("{} · 本群", name),
+                None => format!("{} · 全部聊天", name),
+            }
+        } else {
+            format!("群 {}", group_id.unwrap_or_default())
+        };
+
+        info!(target: "Plugin/AnnualReport", "收到年度报告请求: {} ({})", title, subtitle);
+
+        let config: Config = get_config(&ctx, "annual_report")
+            .unwrap_or_else(|| Deserialize::deserialize(default_config()).unwrap());
+
+        let _ = send_msg(
+            &ctx,
+            writer.clone(),
+            group_id,
+            Some(user_id),
+            Message::new()
+                .reply(message_id)
+                .text(format!("正在生成 {}...", title)),
+        )
+        .await;
+
+        // 查询与渲染耗时较长，分离到后台执行
+        let task_ctx = ctx.clone();
+        detach("annual_report", async move {
+            let result: anyhow::Result<String> = async {
+                let report = data::collect(
+                    &task_ctx.db,
+                    group_id,
+                    query_user,
+                    start,
+                    end,
+                    config.top_words,
+                )
+                .await?;
+                if report.total == 0 {
+                    return Err(anyhow::anyhow!("这一年还没有聊天记录"));
+                }
+                let html = template::render_html(&title, &subtitle, &report, query_user.is_some());
+                render(&html, &config).await
+            }
+            .await;
+
+            let reply = match result {
+                Ok(b64) => Message::new().image(format!("base64://{}", b64)),
+                Err(e) => {
+                    error!(target: "Plugin/AnnualReport", "生成年度报告失败: {}", e);
+                    Message::new()
+                        .reply(message_id)
+                        .text(format!("生成失败: {}", e))
+                }
+            };
+            if let Err(e) = send_msg(&task_ctx, writer, group_id, Some(user_id), reply).await {
+                error!(target: "Plugin/AnnualReport", "发送年度报告失败: {}", e);
+            }
+        });
+
+        Ok(None)
+    })
+}
+
+/// 使用全局浏览器实例将 HTML 渲染为长图
+async fn render(html: &str, config: &Config) -> anyhow::Result<String> {
+    let browser = Browser::instance().await;
+    let tab = browser.new_tab().await?;
+
+    let result: anyhow::Result<String> = async {
+        tab.set_viewport(&Viewport::new(config.width, 100).with_device_scale_factor(2.0))
+            .await?;
+        tab.set_content(html).await?;
+
+        time::sleep(Duration::from_millis(300)).await;
+
+        let height_js = "document.querySelector('.report').scrollHeight";
+        let height = tab.evaluate(height_js).await?.as_f64().unwrap_or(1200.0) as u32;
+        let height = height.clamp(100, config.max_height);
+
+        let viewport = Viewport::new(config.width, height).with_device_scale_factor(2.0);
+        tab.set_viewport(&viewport).await?;
+
+        time::sleep(Duration::from_millis(100)).await;
+
+        let opts = CaptureOptions::new()
+            .with_viewport(viewport)
+            .with_quality(90);
+        let b64 = tab
+            .find_element(".report")
+            .await?
+            .screenshot_with_options(opts)
+            .await?;
+        Ok(b64)
+    }
+    .await;
+
+    let _ = tab.close().await;
+    result
+}
@@ -0,0 +1,164 @@
+use crate::db::queries::{self, MessageTypeStats, UserRanking};
+use crate::plugins::recorder::entity;
+use crate::plugins::word_cloud::stopwords::is_meaningful;
+use chrono::NaiveDate;
+use sea_orm::{DatabaseConnection, DbErr};
+use std::collections::HashMap;
+
+/// 个人名次的统计范围 (龙王榜查询本身最多返回 50 名)
+pub const RANK_SCAN_LIMIT: u64 = 50;
+
+/// 夜猫子时段: 0:00 ~ 5:59
+const NIGHT_HOURS: std::ops::Range<usize> = 0..6;
+
+/// 年度报告所需的全部数据
+pub struct ReportData {
+    pub group_id: Option<i64>,
+    pub total: u64,
+    pub active_days: usize,
+    pub busiest_day: Option<(NaiveDate, i64)>,
+    pub longest_streak: usize,
+    pub hours: [i64; 24],
+    pub peak_hour: usize,
+    pub night_ratio: f64,
+    pub top_words: Vec<(String, usize)>,
+    pub types: MessageTypeStats,
+    pub first_message: Option<entity::Model>,
+    /// 个人报告: 群内名次，未进入前 RANK_SCAN_LIMIT 名时为 None
+    pub my_rank: Option<usize>,
+    /// 群报告: 龙王榜
+    pub top_speakers: Vec<UserRanking>,
+    /// 群报告: 表情包达人
+    pub top_emoji_users: Vec<UserRanking>,
+}
+
+/// 收集报告数据
+/// group_id 为 None 时统计用户在所有群与私聊中的数据；user_id 为 None 时生成群报告
+pub async fn collect(
+    db: &DatabaseConnection,
+    group_id: Option<i64>,
+    user_id: Option<i64>,
+    start: i64,
+    end: i64,
+    word_limit: usize,
+) -> Result<ReportData, DbErr> {
+    let total = queries::get_message_count(db, group_id, user_id, start, end).await?;
+
+    // 每日走势 -> 活跃天数、最忙的一天、最长连续发言
+    let trend = queries::get_daily_trend(db, group_id, user_id, start, end).await?;
+    let days: Vec<(NaiveDate, i64)> = trend
+        .into_iter()
+        .filter(|t| t.count > 0)
+        .filter_map(|t| {
+            NaiveDate::parse_from_str(&t.date, "%Y-%m-%d")
+                .ok()
+                .map(|d| (d, t.count))
+        })
+        .collect();
+    let busiest_day = days
+        .iter()
+        .copied()
+        .max_by_key(|&(d, c)| (c, std::cmp::Reverse(d)));
+    let longest_streak = longest_streak(&days);
+
+    // 小时分布 -> 最活跃时段、夜猫子指数
+    let mut hours = [0i64; 24];
+    for h in queries::get_hourly_activity(db, group_id, user_id, start, end).await? {
+        if let Some(slot) = hours.get_mut(h.hour as usize) {
+            *slot = h.count;
+        }
+    }
+    let peak_hour = (0..24)
+        .max_by_key(|&h| (hours[h], std::cmp::Reverse(h)))
+        .unwrap_or(0);
+    let hour_total: i64 = hours.iter().sum();
+    let night_ratio = if hour_total > 0 {
+        hours[NIGHT_HOURS].iter().sum::<i64>() as f64 / hour_total as f64
+    } else {
+        0.0
+    };
+
+    let corpus = queries::get_text_corpus(db, group_id, user_id, start, end).await?;
+    let top_words = count_words(&corpus, word_limit);
+
+    let types = queries::get_message_type_stats(db, group_id, user_id, start, end).await?;
+    let first_message = queries::get_first_message(db, group_id, user_id, start, end).await?;
+
+    let mut my_rank = None;
+    let mut top_speakers = Vec::new();
+    let mut top_emoji_users = Vec::new();
+
+    match (group_id, user_id) {
+        (Some(gid), Some(uid)) => {
+            let ranking =
+                queries::get_user_ranking(db, Some(gid), start, end, RANK_SCAN_LIMIT).await?;
+            my_rank = ranking
+                .iter()
+                .position(|r| r.user_id == uid)
+                .map(|pos| pos + 1);
+        }
+        (Some(gid), None) => {
+            top_speakers = queries::get_user_ranking(db, Some(gid), start, end, 5).await?;
+            top_emoji_users = queries::get_user_emoji_ranking(db, Some(gid), start, end, 3)
+                .await?
+                .into_iter()
+                .filter(|r| r.count > 0)
+                .collect();
+        }
+        _ => {}
+    }
+
+    Ok(ReportData {
+        group_id,
+        total,
+        active_days: days.len(),
+        busiest_day,
+        longest_streak,
+        hours,
+        peak_hour,
+        night_ratio,
+        top_words,
+        types,
+        first_message,
+        my_rank,
+        top_speakers,
+        top_emoji_users,
+    })
+}
+
+/// 最长连续发言天数 (days 已按日期升序)
+fn longest_streak(days: &[(NaiveDate, i64)]) -> usize {
+    let mut best = 0;
+    let mut current = 0;
+    let mut prev: Option<NaiveDate> = None;
+
+    for &(date, _) in days {
+        current = match prev {
+            Some(p) if p.succ_opt() == Some(date) => current + 1,
+            _ => 1,
+        };
+        best = best.max(current);
+        prev = Some(date);
+    }
+    best
+}
+
+/// 统计高频词 (过滤规则与词云一致)
+fn count_words(corpus: &[String], limit: usize) -> Vec<(String, usize)> {
+    let mut freq: HashMap<&str, usize> = HashMap::new();
+
+    for line in corpus {
+        for w in line.split_whitespace() {
+            let w = w.trim();
+            if is_meaningful(w) {
+                *freq.entry(w).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut words: Vec<(String, usize)> =
+        freq.into_iter().map(|(w, c)| (w.to_string(), c)).collect();
+    words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    words.truncate(limit);
+    words
+}
@@ -0,0 +1,200 @@
+use super::data::{RANK_SCAN_LIMIT, ReportData};
+use crate::db::queries::UserRanking;
+use crate::plugins::escape_html;
+use chrono::{Local, TimeZone};
+use std::fmt::Write;
+
+const TEMPLATE: &str = include_str!("../../../res/annual_report/report.html");
+
+/// 首条消息展示的最大字符数
+const QUOTE_MAX_CHARS: usize = 120;
+
+/// 将报告数据填充到 HTML 模板
+pub fn render_html(title: &str, subtitle: &str, data: &ReportData, personal: bool) -> String {
+    let mut sections = String::new();
+
+    overview(&mut sections, data);
+    highlights(&mut sections, data);
+    hours(&mut sections, data);
+    words(&mut sections, data);
+    stickers(&mut sections, data, personal);
+    first_message(&mut sections, data, personal);
+    if !personal {
+        ranking(&mut sections, "🐉 年度龙王", &data.top_speakers, "条");
+        ranking(&mut sections, "🤡 表情包达人", &data.top_emoji_users, "张");
+    } else if data.group_id.is_some() {
+        my_rank(&mut sections, data);
+    }
+
+    let footer = format!("生成于 {}", Local::now().format("%Y-%m-%d %H:%M"));
+
+    TEMPLATE
+        .replace("{{title}}", &escape_html(title))
+        .replace("{{subtitle}}", &escape_html(subtitle))
+        .replace("{{footer}}", &footer)
+        .replace("{{sections}}", &sections)
+}
+
+fn overview(out: &mut String, data: &ReportData) {
+    let _ = write!(
+        out,
+        r#"<div class="card"><h2>📊 年度总览</h2><div class="stats">{}{}{}</div></div>"#,
+        stat(&data.total.to_string(), "条消息"),
+        stat(&data.active_days.to_string(), "天有发言"),
+        stat(&data.longest_streak.to_string(), "天最长连续发言"),
+    );
+}
+
+fn stat(value: &str, label: &str) -> String {
+    format!(
+        r#"<div class="stat"><div class="value">{}</div><div class="label">{}</div></div>"#,
+        value, label
+    )
+}
+
+fn highlights(out: &mut String, data: &ReportData) {
+    out.push_str(r#"<div class="card"><h2>✨ 高光时刻</h2>"#);
+    if let Some((date, count)) = data.busiest_day {
+        let _ = write!(
+            out,
+            r#"<div class="line">最热闹的一天是 <b>{}</b>，这一天发了 <b>{}</b> 条消息</div>"#,
+            date.format("%Y年%m月%d日"),
+            count
+        );
+    }
+    let _ = write!(
+        out,
+        r#"<div class="line">最活跃的时段是 <b>{:02}:00 ~ {:02}:59</b></div>"#,
+        data.peak_hour, data.peak_hour
+    );
+    let _ = write!(
+        out,
+        r#"<div class="line">夜猫子指数 <b>{:.1}%</b> <span class="empty">(凌晨 0 点至 6 点的消息占比)</span></div>"#,
+        data.night_ratio * 100.0
+    );
+    out.push_str("</div>");
+}
+
+fn hours(out: &mut String, data: &ReportData) {
+    let max = data.hours.iter().copied().max().unwrap_or(0).max(1);
+
+    out.push_str(r#"<div class="card"><h2>🕐 24 小时分布</h2><div class="hours">"#);
+    for (hour, &count) in data.hours.iter().enumerate() {
+        let class = if count > 0 && hour == data.peak_hour {
+            "bar peak"
+        } else if hour < 6 {
+            "bar night"
+        } else {
+            "bar"
+        };
+        let _ = write!(
+            out,
+            r#"<div class="{}" style="height:{:.1}%"></div>"#,
+            class,
+            count as f64 / max as f64 * 100.0
+        );
+    }
+    out.push_str(r#"</div><div class="hour-labels">"#);
+    for hour in 0..24 {
+        if hour % 3 == 0 {
+            let _ = write!(out, "<span>{}</span>", hour);
+        } else {
+            out.push_str("<span></span>");
+        }
+    }
+    out.push_str("</div></div>");
+}
+
+fn words(out: &mut String, data: &ReportData) {
+    out.push_str(r#"<div class="card"><h2>💬 年度热词</h2>"#);
+    if data.top_words.is_empty() {
+        out.push_str(r#"<div class="empty">没有足够的文字记录</div>"#);
+    } else {
+        out.push_str(r#"<div class="words">"#);
+        for (word, count) in &data.top_words {
+            let _ = write!(
+                out,
+                r#"<span class="word">{}<small>{}</small></span>"#,
+                escape_html(word),
+                count
+            );
+        }
+        out.push_str("</div>");
+    }
+    out.push_str("</div>");
+}
+
+fn stickers(out: &mut String, data: &ReportData, personal: bool) {
+    let who = if personal { "你" } else { "大家" };
+    let t = &data.types;
+    let _ = write!(
+        out,
+        r#"<div class="card"><h2>😂 表情与图片</h2><div class="stats">{}{}{}</div><div class="line" style="margin-top:10px">这一年{}一共发了 <b>{}</b> 次表情</div></div>"#,
+        stat(&t.anim_emoji.to_string(), "张表情包"),
+        stat(&t.face.to_string(), "个 QQ 表情"),
+        stat(&t.image.to_string(), "张图片"),
+        who,
+        t.anim_emoji + t.face
+    );
+}
+
+fn first_message(out: &mut String, data: &ReportData, personal: bool) {
+    out.push_str(r#"<div class="card"><h2>🌱 年度第一句话</h2>"#);
+    match &data.first_message {
+        Some(m) => {
+            let mut content: String = m.content_rich.chars().take(QUOTE_MAX_CHARS).collect();
+            if m.content_rich.chars().count() > QUOTE_MAX_CHARS {
+                content.push('…');
+            }
+            let time = Local
+                .timestamp_opt(m.time, 0)
+                .single()
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            let mut meta = time;
+            if !personal {
+                let _ = write!(meta, " · {}", escape_html(m.display_name()));
+            } else if !m.group_name.is_empty() {
+                let _ = write!(meta, " · {}", escape_html(&m.group_name));
+            }
+            let _ = write!(
+                out,
+                r#"<div class="quote">{}<div class="meta">{}</div></div>"#,
+                escape_html(&content),
+                meta
+            );
+        }
+        None => out.push_str(r#"<div class="empty">暂无记录</div>"#),
+    }
+    out.push_str("</div>");
+}
+
+fn my_rank(out: &mut String, data: &ReportData) {
+    let text = match data.my_rank {
+        Some(rank) => format!("你是本群年度发言第 <b>{}</b> 名", rank),
+        None => format!("你没有进入本群年度发言前 {} 名", RANK_SCAN_LIMIT),
+    };
+    let _ = write!(
+        out,
+        r#"<div class="card"><h2>🏆 群内排名</h2><div class="line">{}</div></div>"#,
+        text
+    );
+}
+
+fn ranking(out: &mut String, title: &str, list: &[UserRanking], unit: &str) {
+    let _ = write!(out, r#"<div class="card"><h2>{}</h2>"#, title);
+    if list.is_empty() {
+        out.push_str(r#"<div class="empty">暂无记录</div>"#);
+    }
+    for (i, r) in list.iter().enumerate() {
+        let _ = write!(
+            out,
+            r#"<div class="rank"><span><span class="no">{}</span>{}</span><span>{} {}</span></div>"#,
+            i + 1,
+            escape_html(&r.nickname),
+            r.count,
+            unit
+        );
+    }
+    out.push_str("</div>");
+}
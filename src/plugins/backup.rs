@@ -0,0 +1,161 @@
+use crate::adapters::onebot::{LockedWriter, send_msg};
+use crate::command::match_command;
+use crate::config::{build_config, data_dir};
+use crate::db::backup;
+use crate::event::Context;
+use crate::message::Message;
+use crate::plugins::{PluginError, get_config, validate_as};
+use futures_util::future::BoxFuture;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use toml::Value;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Config {
+    enabled: bool,
+    /// 每日备份时间 (时)
+    #[serde(default = "default_hour")]
+    hour: u32,
+    /// 每日备份时间 (分)
+    #[serde(default = "default_minute")]
+    minute: u32,
+    /// 保留的快照数量 (0 表示全部保留)
+    #[serde(default = "default_keep")]
+    keep: usize,
+    /// 快照目录，为空时使用 <数据目录>/backups
+    #[serde(default)]
+    dir: String,
+}
+
+fn default_hour() -> u32 {
+    3
+}
+
+fn default_minute() -> u32 {
+    30
+}
+
+fn default_keep() -> usize {
+    7
+}
+
+pub fn default_config() -> Value {
+    build_config(Config {
+        enabled: true,
+        hour: default_hour(),
+        minute: default_minute(),
+        keep: default_keep(),
+        dir: String::new(),
+    })
+}
+
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
+fn load_config(ctx: &Context) -> Config {
+    get_config(ctx, "backup").unwrap_or_else(|| Deserialize::deserialize(default_config()).unwrap())
+}
+
+fn backup_dir(config: &Config) -> PathBuf {
+    if config.dir.is_empty() {
+        data_dir().join("backups")
+    } else {
+        PathBuf::from(&config.dir)
+    }
+}
+
+/// 执行一次备份并清理旧快照，返回给管理员的结果描述
+async fn run_backup(db: &DatabaseConnection, config: &Config) -> String {
+    let dir = backup_dir(config);
+
+    match backup::snapshot(db, &dir).await {
+        Ok(Some(path)) => {
+            let size = tokio::fs::metadata(&path)
+                .await
+                .map(|m| m.len())
+                .unwrap_or(0);
+            info!(
+                target: "Plugin/Backup",
+                "数据库快照已保存: {} ({:.2} MB)",
+                path.display(),
+                size as f64 / 1024.0 / 1024.0
+            );
+
+            match backup::prune_snapshots(&dir, config.keep) {
+                Ok(n) if n > 0 => info!(target: "Plugin/Backup", "已删除 {} 份旧快照", n),
+                Ok(_) => {}
+                Err(e) => warn!(target: "Plugin/Backup", "清理旧快照失败: {}", e),
+            }
+
+            format!("✅ 数据库已备份\n{}", path.display())
+        }
+        Ok(None) => {
+            warn!(target: "Plugin/Backup", "当前数据库不是 SQLite，跳过快照，请使用数据库自带的备份工具");
+            "⚠️ 当前数据库不是 SQLite，请使用数据库自带的备份工具".to_string()
+        }
+        Err(e) => {
+            error!(target: "Plugin/Backup", "数据库备份失败: {}", e);
+            format!("❌ 数据库备份失败: {}", e)
+        }
+    }
+}
+
+pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
+    Box::pin(async move {
+        let config = load_config(&ctx);
+
+        let db = ctx.db.clone();
+        let cfg = ctx.clone();
+        ctx.scheduler
+            .add_daily_at(config.hour, config.minute, 0, move || {
+                let db = db.clone();
+                // 每次执行时读取最新配置 (保留数量、目录可热更新)
+                let config = load_config(&cfg);
+                async move {
+                    run_backup(&db, &config).await;
+                }
+            });
+
+        info!(
+            target: "Plugin/Backup",
+            "每日 {:02}:{:02} 备份数据库到 {}",
+            config.hour,
+            config.minute,
+            backup_dir(&config).display()
+        );
+        Ok(())
+    })
+}
+
+pub fn handle(
+    ctx: Context,
+    writer: LockedWriter,
+) -> BoxFuture<'static, Result<Option<Context>, PluginError>> {
+    Box::pin(async move {
+        if match_command(&ctx, "备份数据库").is_none() {
+            return Ok(Some(ctx));
+        }
+
+        let msg = match ctx.as_message() {
+            Some(m) => m,
+            None => return Ok(Some(ctx)),
+        };
+        let group_id = msg.group_id();
+        let user_id = msg.user_id();
+        let message_id = msg.message_id();
+
+        let is_superuser = ctx.config.read().unwrap().superusers.contains(&user_id);
+        if !is_superuser {
+            return Ok(Some(ctx));
+        }
+
+        let result = run_backup(&ctx.db, &load_config(&ctx)).await;
+        let reply = Message::new().reply(message_id).text(result);
+        send_msg(&ctx, writer, group_id, Some(user_id), reply).await?;
+
+        Ok(None)
+    })
+}
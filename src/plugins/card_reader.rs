@@ -2,7 +2,7 @@ use crate::adapters::onebot::{LockedWriter, api, send_msg};
 use crate::command::match_command;
 use crate::config::build_config;
 use crate::event::Context;
-use crate::plugins::{PluginError, get_config, get_data_dir};
+use crate::plugins::{PluginError, get_config, get_data_dir, validate_as};
 use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use simd_json::OwnedValue;
@@ -32,6 +32,11 @@ pub fn default_config() -> Value {
     })
 }
 
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
 // =============================
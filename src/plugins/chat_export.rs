@@ -0,0 +1,256 @@
+use crate::adapters::onebot::{LockedWriter, api, send_msg};
+use crate::command::match_command;
+use crate::config::build_config;
+use crate::db::queries;
+use crate::db::utils::parse_time_range;
+use crate::dispatcher::detach;
+use crate::event::Context;
+use crate::message::Message;
+use crate::plugins::recorder::raw;
+use crate::plugins::{PluginError, get_config, get_data_dir, validate_as};
+use chrono::{Local, TimeZone};
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use simd_json::OwnedValue;
+use simd_json::derived::{ValueObjectAccess, ValueObjectAccessAsScalar};
+use std::collections::HashMap;
+use toml::Value;
+use transcript::{Format, TranscriptWriter};
+
+mod transcript;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Config {
+    enabled: bool,
+    /// 默认导出格式: html / txt / json
+    #[serde(default = "default_format")]
+    format: String,
+    /// 是否允许群主与管理员导出 (超级用户始终允许)
+    #[serde(default = "default_true")]
+    allow_group_admin: bool,
+    /// 每次从数据库读取的记录数
+    #[serde(default = "default_batch_size")]
+    batch_size: u64,
+}
+
+fn default_format() -> String {
+    "html".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_batch_size() -> u64 {
+    1000
+}
+
+pub fn default_config() -> Value {
+    build_config(Config {
+        enabled: true,
+        format: default_format(),
+        allow_group_admin: true,
+        batch_size: default_batch_size(),
+    })
+}
+
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
+const USAGE: &str = "用法: 导出聊天记录 [时间范围] [html|txt|json]\n例如: 导出聊天记录 近7天 txt";
+
+pub fn handle(
+    ctx: Context,
+    writer: LockedWriter,
+) -> BoxFuture<'static, Result<Option<Context>, PluginError>> {
+    Box::pin(async move {
+        let Some(cmd) = match_command(&ctx, "导出聊天记录") else {
+            return Ok(Some(ctx));
+        };
+        let msg = ctx.as_message().unwrap();
+        let user_id = msg.user_id();
+        let message_id = msg.message_id();
+        let Some(group_id) = msg.group_id() else {
+            let reply = Message::new().text("请在群聊中使用“导出聊天记录”。");
+            send_msg(&ctx, writer, None, Some(user_id), reply).await?;
+            return Ok(None);
+        };
+
+        let config: Config = get_config(&ctx, "chat_export")
+            .unwrap_or_else(|| Deserialize::deserialize(default_config()).unwrap());
+
+        let is_superuser = ctx.config.read().unwrap().superusers.contains(&user_id);
+        let is_admin = matches!(msg.sender_role(), Some("owner" | "admin"));
+        if !is_superuser && !(config.allow_group_admin && is_admin) {
+            let reply = Message::new()
+                .reply(message_id)
+                .text("只有群主、管理员或超级用户可以导出聊天记录。");
+            send_msg(&ctx, writer, Some(group_id), Some(user_id), reply).await?;
+            return Ok(None);
+        }
+
+        // 参数: 时间范围与格式，顺序不限
+        let mut time_str = None;
+        let mut format = None;
+        for arg in extract_text(&cmd.args).split_whitespace() {
+            if let Some(f) = Format::parse(arg) {
+                format = Some(f);
+            } else if parse_time_range(arg).is_some() {
+                time_str = Some(arg.to_string());
+            } else {
+                let reply = Message::new().reply(message_id).text(USAGE);
+                send_msg(&ctx, writer, Some(group_id), Some(user_id), reply).await?;
+                return Ok(None);
+            }
+        }
+        let time_str = time_str.unwrap_or_else(|| "今日".to_string());
+        let format = format
+            .or_else(|| Format::parse(&config.format))
+            .unwrap_or(Format::Html);
+        let Some((start, end)) = parse_time_range(&time_str) else {
+            return Ok(Some(ctx));
+        };
+
+        info!(target: "Plugin/ChatExport", "导出群 {} 聊天记录: {} ({})", group_id, time_str, format.extension());
+
+        let _ = send_msg(
+            &ctx,
+            writer.clone(),
+            Some(group_id),
+            Some(user_id),
+            Message::new()
+                .reply(message_id)
+                .text(format!("正在导出{}的聊天记录...", time_str)),
+        )
+        .await;
+
+        // 大范围导出耗时较长，分离到后台执行
+        let task_ctx = ctx.clone();
+        detach("chat_export", async move {
+            let dir = match get_data_dir("chat_export").await {
+                Ok(d) => d,
+                Err(e) => {
+                    error!(target: "Plugin/ChatExport", "创建导出目录失败: {}", e);
+                    return;
+                }
+            };
+            let file_name = format!(
+                "chat_{}_{}-{}.{}",
+                group_id,
+                date_label(start),
+                date_label(end - 1),
+                format.extension()
+            );
+            let path = dir.join(&file_name);
+
+            let title = format!("群 {} 聊天记录", group_id);
+            let subtitle = format!(
+                "{} ({} 至 {})，导出于 {}",
+                time_str,
+                date_label(start),
+                date_label(end - 1),
+                Local::now().format("%Y-%m-%d %H:%M")
+            );
+
+            let result = async {
+                let mut out = TranscriptWriter::create(&path, format, &title, &subtitle).await?;
+                let mut after_id = 0;
+                loop {
+                    let batch = queries::get_group_messages_after(
+                        &task_ctx.db,
+                        group_id,
+                        start,
+                        end,
+                        after_id,
+                        config.batch_size.max(1),
+                    )
+                    .await?;
+                    let Some(last) = batch.last() else {
+                        break;
+                    };
+                    after_id = last.id;
+
+                    let mut raws: HashMap<i32, OwnedValue> = HashMap::new();
+                    if format.needs_raw() {
+                        let ids: Vec<i32> = batch.iter().map(|r| r.id).collect();
+                        for row in raw::find_by_record_ids(&task_ctx.db, &ids).await? {
+                            if let Some(message) = row.message() {
+                                raws.insert(row.record_id, message);
+                            }
+                        }
+                    }
+
+                    for record in &batch {
+                        out.write(record, raws.get(&record.id)).await?;
+                    }
+                }
+                Ok::<u64, anyhow::Error>(out.finish().await?)
+            }
+            .await;
+
+            let reply = match result {
+                Ok(0) => Some(format!("{}没有聊天记录。", time_str)),
+                Ok(count) => {
+                    info!(target: "Plugin/ChatExport", "已导出 {} 条记录到 {}", count, path.display());
+                    match api::upload_file(
+                        &task_ctx,
+                        writer.clone(),
+                        Some(group_id),
+                        Some(user_id),
+                        &path.to_string_lossy(),
+                        &file_name,
+                    )
+                    .await
+                    {
+                        Ok(()) => None,
+                        Err(e) => {
+                            error!(target: "Plugin/ChatExport", "上传导出文件失败: {}", e);
+                            Some(format!("文件上传失败: {}", e))
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!(target: "Plugin/ChatExport", "导出聊天记录失败: {}", e);
+                    Some(format!("导出失败: {}", e))
+                }
+            };
+
+            let _ = tokio::fs::remove_file(&path).await;
+
+            if let Some(text) = reply {
+                let reply = Message::new().reply(message_id).text(text);
+                if let Err(e) =
+                    send_msg(&task_ctx, writer, Some(group_id), Some(user_id), reply).await
+                {
+                    error!(target: "Plugin/ChatExport", "发送导出结果失败: {}", e);
+                }
+            }
+        });
+
+        Ok(None)
+    })
+}
+
+/// 拼接参数中的文本片段
+fn extract_text(args: &[OwnedValue]) -> String {
+    let mut text = String::new();
+    for seg in args {
+        if seg.get_str("type") == Some("text")
+            && let Some(t) = seg.get("data").and_then(|d| d.get_str("text"))
+        {
+            text.push_str(t);
+            text.push(' ');
+        }
+    }
+    text
+}
+
+fn date_label(ts: i64) -> String {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|t| t.format("%Y%m%d").to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,300 @@
+use crate::plugins::escape_html;
+use crate::plugins::recorder::entity::Model as Record;
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{Local, TimeZone};
+use simd_json::OwnedValue;
+use simd_json::base::{ValueAsArray, ValueAsScalar};
+use simd_json::derived::{ValueObjectAccess, ValueObjectAccessAsScalar};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width,initial-scale=1">
+<title>{{title}}</title>
+<style>
+body{margin:0;padding:20px;background:#f3f4f6;font-family:-apple-system,BlinkMacSystemFont,"Segoe UI","PingFang SC","Microsoft YaHei",sans-serif;color:#222}
+h1{font-size:20px;margin:0 0 4px}
+.sub{color:#888;font-size:13px;margin-bottom:16px}
+.msg{background:#fff;border-radius:8px;padding:8px 12px;margin-bottom:8px}
+.meta{font-size:12px;color:#888;margin-bottom:4px}
+.meta .name{color:#3b5bdb;font-weight:600}
+.meta .self{color:#c2255c}
+.content{font-size:14px;line-height:1.6;white-space:pre-wrap;word-break:break-all}
+.content img{display:block;max-width:320px;max-height:320px;margin:4px 0;border-radius:4px}
+.tag{color:#888}
+</style>
+</head>
+<body>
+<h1>{{title}}</h1>
+<div class="sub">{{subtitle}}</div>
+"#;
+
+const HTML_TAIL: &str = "</body>\n</html>\n";
+
+/// 内联图片的单张大小上限，超出时显示为 [图片]
+const MAX_INLINE_IMAGE: usize = 5 * 1024 * 1024;
+
+/// 下载单张图片的超时时间
+const IMAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 导出格式
+#[derive(Clone, Copy)]
+pub enum Format {
+    Html,
+    Txt,
+    Json,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "html" | "网页" => Some(Self::Html),
+            "txt" | "文本" => Some(Self::Txt),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Txt => "txt",
+            Self::Json => "json",
+        }
+    }
+
+    /// 是否需要读取原始消息段
+    pub fn needs_raw(&self) -> bool {
+        !matches!(self, Self::Txt)
+    }
+}
+
+/// 逐条写入聊天记录的文件写入器，不在内存中保留已写出的内容
+pub struct TranscriptWriter {
+    format: Format,
+    out: BufWriter<File>,
+    count: u64,
+    /// HTML 导出时用于下载图片
+    client: reqwest::Client,
+}
+
+impl TranscriptWriter {
+    pub async fn create(
+        path: &Path,
+        format: Format,
+        title: &str,
+        subtitle: &str,
+    ) -> std::io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path).await?);
+        match format {
+            Format::Html => {
+                let head = HTML_HEAD
+                    .replace("{{title}}", &escape_html(title))
+                    .replace("{{subtitle}}", &escape_html(subtitle));
+                out.write_all(head.as_bytes()).await?;
+            }
+            Format::Txt => {
+                let head = format!("{}\n{}\n{}\n\n", title, subtitle, "=".repeat(40));
+                out.write_all(head.as_bytes()).await?;
+            }
+            Format::Json => out.write_all(b"[\n").await?,
+        }
+        let client = reqwest::Client::builder()
+            .timeout(IMAGE_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Ok(Self {
+            format,
+            out,
+            count: 0,
+            client,
+        })
+    }
+
+    pub async fn write(&mut self, r: &Record, raw: Option<&OwnedValue>) -> std::io::Result<()> {
+        let text = match self.format {
+            Format::Html => html_entry(&self.client, r, raw).await,
+            Format::Txt => txt_entry(r),
+            Format::Json => {
+                let prefix = if self.count == 0 { "" } else { ",\n" };
+                format!("{}{}", prefix, json_entry(r, raw))
+            }
+        };
+        self.out.write_all(text.as_bytes()).await?;
+        self.count += 1;
+        Ok(())
+    }
+
+    pub async fn finish(mut self) -> std::io::Result<u64> {
+        match self.format {
+            Format::Html => self.out.write_all(HTML_TAIL.as_bytes()).await?,
+            Format::Txt => {}
+            Format::Json => self.out.write_all(b"\n]\n").await?,
+        }
+        self.out.flush().await?;
+        Ok(self.count)
+    }
+}
+
+fn format_time(ts: i64) -> String {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn txt_entry(r: &Record) -> String {
+    // 多行消息缩进对齐，便于阅读
+    let content = r.content_rich.replace('\n', "\n    ");
+    format!(
+        "[{}] {}({}): {}\n",
+        format_time(r.time),
+        r.display_name(),
+        r.user_id,
+        content
+    )
+}
+
+fn json_entry(r: &Record, raw: Option<&OwnedValue>) -> String {
+    let mut entry = serde_json::json!({
+        "id": r.id,
+        "time": r.time,
+        "user_id": r.user_id,
+        "user_name": r.user_name,
+        "sender_nick": r.sender_nick,
+        "role": r.role,
+        "content": r.content_rich,
+    });
+    if let Some(raw) = raw
+        && let Ok(segments) = serde_json::to_value(raw)
+    {
+        entry["message"] = segments;
+    }
+    format!("  {}", entry)
+}
+
+async fn html_entry(client: &reqwest::Client, r: &Record, raw: Option<&OwnedValue>) -> String {
+    let name_class = if r.role == "self" {
+        "name self"
+    } else {
+        "name"
+    };
+    let content = match raw {
+        Some(raw) => render_segments(client, raw).await,
+        None => escape_html(&r.content_rich),
+    };
+    format!(
+        r#"<div class="msg"><div class="meta"><span class="{}">{}</span> ({}) · {}</div><div class="content">{}</div></div>
+"#,
+        name_class,
+        escape_html(r.display_name()),
+        r.user_id,
+        format_time(r.time),
+        content
+    )
+}
+
+/// 将原始消息段渲染为 HTML
+/// QQ 图片链接会过期，图片下载后以 data: URI 内联到文件中，下载失败时显示为 [图片]
+async fn render_segments(client: &reqwest::Client, raw: &OwnedValue) -> String {
+    if let Some(s) = raw.as_str() {
+        return escape_html(s);
+    }
+    let Some(arr) = raw.as_array() else {
+        return String::new();
+    };
+
+    let mut out = String::new();
+    for seg in arr {
+        let type_ = seg.get_str("type").unwrap_or("unknown");
+        let data = seg.get("data");
+        let field = |key: &str| {
+            data.and_then(|d| {
+                d.get_str(key)
+                    .map(str::to_string)
+                    .or_else(|| d.get_i64(key).map(|v| v.to_string()))
+                    .or_else(|| d.get_u64(key).map(|v| v.to_string()))
+            })
+        };
+        match type_ {
+            "text" => out.push_str(&escape_html(&field("text").unwrap_or_default())),
+            "image" => {
+                let src = field("url").or_else(|| field("file"));
+                match src {
+                    Some(src) => match inline_image(client, &src).await {
+                        Some(uri) => {
+                            let _ = write!(out, r#"<img src="{}" loading="lazy">"#, uri);
+                        }
+                        None => out.push_str(r#"<span class="tag">[图片]</span>"#),
+                    },
+                    None => out.push_str(r#"<span class="tag">[图片]</span>"#),
+                }
+            }
+            "at" => {
+                let _ = write!(
+                    out,
+                    r#"<span class="tag">@{}</span>"#,
+                    escape_html(&field("qq").unwrap_or_default())
+                );
+            }
+            "reply" => {
+                let _ = write!(
+                    out,
+                    r#"<span class="tag">[回复 #{}]</span> "#,
+                    escape_html(&field("id").unwrap_or_default())
+                );
+            }
+            "face" => out.push_str(r#"<span class="tag">[表情]</span>"#),
+            other => {
+                let _ = write!(out, r#"<span class="tag">[{}]</span>"#, escape_html(other));
+            }
+        }
+    }
+    out
+}
+
+/// 获取图片内容并转换为 data: URI，MIME 类型按文件内容识别
+async fn inline_image(client: &reqwest::Client, src: &str) -> Option<String> {
+    let bytes = if let Some(b64) = src.strip_prefix("base64://") {
+        general_purpose::STANDARD.decode(b64).ok()?
+    } else if src.starts_with("http") {
+        let resp = match client
+            .get(src)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+        {
+            Ok(resp) => resp,
+            Err(e) => {
+                debug!(target: "Plugin/ChatExport", "下载图片失败 {}: {}", src, e);
+                return None;
+            }
+        };
+        if resp
+            .content_length()
+            .is_some_and(|n| n as usize > MAX_INLINE_IMAGE)
+        {
+            return None;
+        }
+        resp.bytes().await.ok()?.to_vec()
+    } else {
+        return None;
+    };
+
+    if bytes.len() > MAX_INLINE_IMAGE {
+        return None;
+    }
+    let mime = image::guess_format(&bytes).ok()?.to_mime_type();
+    Some(format!(
+        "data:{};base64,{}",
+        mime,
+        general_purpose::STANDARD.encode(&bytes)
+    ))
+}
@@ -0,0 +1,289 @@
+use crate::adapters::onebot::{LockedWriter, send_msg};
+use crate::command::match_command;
+use crate::config::build_config;
+use crate::db::queries;
+use crate::db::utils::parse_time_range;
+use crate::event::Context;
+use crate::message::Message;
+use crate::plugins::recorder::fts;
+use crate::plugins::{PluginError, get_config, validate_as};
+use chrono::{Local, TimeZone};
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use simd_json::OwnedValue;
+use simd_json::derived::{ValueObjectAccess, ValueObjectAccessAsScalar};
+use toml::Value;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Config {
+    enabled: bool,
+    /// 每页展示的结果数量
+    #[serde(default = "default_page_size")]
+    page_size: u64,
+}
+
+fn default_page_size() -> u64 {
+    10
+}
+
+pub fn default_config() -> Value {
+    build_config(Config {
+        enabled: true,
+        page_size: default_page_size(),
+    })
+}
+
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
+/// 单页结果数量上限，避免合并转发消息过长
+const MAX_PAGE_SIZE: u64 = 50;
+/// 片段中命中词之前保留的字符数
+const SNIPPET_LEADING_CHARS: usize = 20;
+/// 片段最大字符数
+const SNIPPET_MAX_CHARS: usize = 80;
+
+const USAGE: &str =
+    "用法: 搜索聊天 <关键词> [时间] [@用户] [第N页]\n例如: 搜索聊天 火锅 近7天 第2页";
+
+/// 解析后的搜索参数
+struct SearchArgs {
+    keyword: String,
+    time: Option<String>,
+    page: u64,
+    target: Option<i64>,
+}
+
+/// 第一个词总是关键词，其后的词依次尝试识别为页码与时间，其余并入关键词
+fn parse_args(args: &[OwnedValue]) -> SearchArgs {
+    let mut text = String::new();
+    let mut target = None;
+    for seg in args {
+        let Some(data) = seg.get("data") else {
+            continue;
+        };
+        match seg.get_str("type") {
+            Some("text") => {
+                text.push_str(data.get_str("text").unwrap_or(""));
+                text.push(' ');
+            }
+            Some("at") => {
+                let qq = data
+                    .get_str("qq")
+                    .and_then(|s| s.parse().ok())
+                    .or_else(|| data.get_i64("qq"))
+                    .or_else(|| data.get_u64("qq").map(|v| v as i64));
+                if target.is_none() {
+                    target = qq;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut keyword = Vec::new();
+    let mut time = None;
+    let mut page = 1;
+    for (i, word) in text.split_whitespace().enumerate() {
+        if i > 0 {
+            if let Some(n) = word
+                .strip_prefix('第')
+                .and_then(|s| s.strip_suffix('页'))
+                .and_then(|s| s.parse::<u64>().ok())
+            {
+                page = n.max(1);
+                continue;
+            }
+            if time.is_none() && parse_time_range(word).is_some() {
+                time = Some(word.to_string());
+                continue;
+            }
+        }
+        keyword.push(word);
+    }
+
+    SearchArgs {
+        keyword: keyword.join(" "),
+        time,
+        page,
+        target,
+    }
+}
+
+pub fn handle(
+    ctx: Context,
+    writer: LockedWriter,
+) -> BoxFuture<'static, Result<Option<Context>, PluginError>> {
+    Box::pin(async move {
+        let Some(cmd) = match_command(&ctx, "搜索聊天") else {
+            return Ok(Some(ctx));
+        };
+        let msg = ctx.as_message().unwrap();
+        let group_id = msg.group_id();
+        let user_id = msg.user_id();
+        let message_id = msg.message_id();
+
+        let args = parse_args(&cmd.args);
+        let words = fts::split_keyword(&args.keyword);
+        if words.is_empty() {
+            let reply = Message::new().reply(message_id).text(USAGE);
+            send_msg(&ctx, writer, group_id, Some(user_id), reply).await?;
+            return Ok(None);
+        }
+
+        let time_str = args.time.as_deref().unwrap_or("总");
+        let Some((start, end)) = parse_time_range(time_str) else {
+            let reply = Message::new().text(format!("无法识别的时间: {}", time_str));
+            send_msg(&ctx, writer, group_id, Some(user_id), reply).await?;
+            return Ok(None);
+        };
+
+        // 群聊中只能搜索本群，私聊中只能搜索自己的消息
+        let query_user = match group_id {
+            Some(_) => args.target,
+            None => Some(user_id),
+        };
+
+        let config: Config = get_config(&ctx, "chat_search")
+            .unwrap_or_else(|| Deserialize::deserialize(default_config()).unwrap());
+        let page_size = config.page_size.clamp(1, MAX_PAGE_SIZE);
+
+        info!(target: "Plugin/ChatSearch", "搜索聊天: {:?} ({}) 第 {} 页", words, time_str, args.page);
+
+        let (records, total) = queries::search_messages(
+            &ctx.db,
+            group_id,
+            query_user,
+            &words,
+            start,
+            end,
+            args.page - 1,
+            page_size,
+        )
+        .await
+        .map_err(|e| format!("DB Query Error: {}", e))?;
+
+        if total == 0 {
+            let reply = Message::new()
+                .reply(message_id)
+                .text(format!("没有找到包含「{}」的消息", args.keyword));
+            send_msg(&ctx, writer, group_id, Some(user_id), reply).await?;
+            return Ok(None);
+        }
+
+        let pages = total.div_ceil(page_size);
+        if records.is_empty() {
+            let reply = Message::new().reply(message_id).text(format!(
+                "搜索结果共 {} 页，第 {} 页不存在",
+                pages, args.page
+            ));
+            send_msg(&ctx, writer, group_id, Some(user_id), reply).await?;
+            return Ok(None);
+        }
+
+        let self_id = ctx.bot.login_user.id.parse::<i64>().unwrap_or(0);
+        let mut forward = Message::new().node_custom(
+            self_id,
+            "聊天搜索",
+            Message::new().text(format!(
+                "搜索「{}」({}) 共 {} 条，第 {}/{} 页",
+                args.keyword, time_str, total, args.page, pages
+            )),
+        );
+
+        for r in &records {
+            let time = Local
+                .timestamp_opt(r.time, 0)
+                .single()
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            let mut header = time;
+            if group_id.is_none() && !r.group_name.is_empty() {
+                header = format!("{} · {}", header, r.group_name);
+            }
+            let nickname = if r.sender_nick.is_empty() {
+                &r.user_name
+            } else {
+                &r.sender_nick
+            };
+            let content = format!("{}\n{}", header, snippet(&r.content_rich, &words));
+            forward = forward.node_custom(r.user_id, nickname, Message::new().text(content));
+        }
+
+        if args.page < pages {
+            let time_arg = args.time.map(|t| format!(" {}", t)).unwrap_or_default();
+            forward = forward.node_custom(
+                self_id,
+                "聊天搜索",
+                Message::new().text(format!(
+                    "发送「搜索聊天 {}{} 第{}页」查看下一页",
+                    args.keyword,
+                    time_arg,
+                    args.page + 1
+                )),
+            );
+        }
+
+        send_msg(&ctx, writer, group_id, Some(user_id), forward).await?;
+        Ok(None)
+    })
+}
+
+/// 截取第一个命中词附近的片段，并用【】标出所有命中词
+/// 仅对 ASCII 做大小写折叠，保证折叠前后字节偏移一致
+fn snippet(text: &str, words: &[String]) -> String {
+    let lower = text.to_ascii_lowercase();
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for word in words {
+        let word = word.to_ascii_lowercase();
+        let mut from = 0;
+        while let Some(pos) = lower[from..].find(&word) {
+            let start = from + pos;
+            ranges.push((start, start + word.len()));
+            from = start + word.len();
+        }
+    }
+    ranges.sort_unstable();
+
+    // 合并重叠的命中区间
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (s, e) in ranges {
+        match merged.last_mut() {
+            Some(last) if s <= last.1 => last.1 = last.1.max(e),
+            _ => merged.push((s, e)),
+        }
+    }
+
+    let offsets: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+    let first = merged.first().map_or(0, |r| r.0);
+    let first_char = offsets.partition_point(|&b| b < first);
+    let start_char = first_char.saturating_sub(SNIPPET_LEADING_CHARS);
+    let end_char = (start_char + SNIPPET_MAX_CHARS).min(offsets.len());
+    let window_start = offsets.get(start_char).copied().unwrap_or(text.len());
+    let window_end = offsets.get(end_char).copied().unwrap_or(text.len());
+
+    let mut out = String::new();
+    if window_start > 0 {
+        out.push('…');
+    }
+    let mut cursor = window_start;
+    for (s, e) in merged {
+        let (s, e) = (s.max(window_start), e.min(window_end));
+        if s >= e || s < cursor {
+            continue;
+        }
+        out.push_str(&text[cursor..s]);
+        out.push('【');
+        out.push_str(&text[s..e]);
+        out.push('】');
+        cursor = e;
+    }
+    out.push_str(&text[cursor..window_end]);
+    if window_end < text.len() {
+        out.push('…');
+    }
+    out
+}
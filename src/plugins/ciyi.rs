@@ -6,13 +6,14 @@ pub mod entity;
 use crate::adapters::onebot::{LockedWriter, send_msg};
 use crate::command::match_command;
 use crate::config::build_config;
+use crate::db::migrations::{Migration, create_index, create_table};
 use crate::event::Context;
 use crate::message::Message;
 use crate::plugins::ciyi::config::CiYiConfig;
 use crate::plugins::ciyi::entity::{record as record_entity, state as state_entity};
-use crate::plugins::{PluginError, get_config};
+use crate::plugins::{PluginError, get_config, validate_as};
 use futures_util::future::BoxFuture;
-use sea_orm::{ConnectionTrait, Schema};
+use sea_orm::{DatabaseTransaction, DbErr};
 use simd_json::derived::{ValueObjectAccess, ValueObjectAccessAsScalar};
 use toml::Value;
 
@@ -20,32 +21,23 @@ pub fn default_config() -> Value {
     build_config(CiYiConfig::default())
 }
 
-pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
-    Box::pin(async move {
-        let db = &ctx.db;
-        let builder = db.get_database_backend();
-        let schema = Schema::new(builder);
-
-        // 1. 创建 State 表
-        let mut create_state_table = schema.create_table_from_entity(state_entity::Entity);
-        if let Err(e) = db
-            .execute(builder.build(create_state_table.if_not_exists()))
-            .await
-        {
-            crate::warn!(target: "Plugin/CiYi", "Init state table error: {}", e);
-        }
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<CiYiConfig>(value)
+}
 
-        // 2. 创建 Record 表
-        let mut create_record_table = schema.create_table_from_entity(record_entity::Entity);
-        if let Err(e) = db
-            .execute(builder.build(create_record_table.if_not_exists()))
-            .await
-        {
-            crate::warn!(target: "Plugin/CiYi", "Init record table error: {}", e);
-        }
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    name: "create_ciyi_tables",
+    up: migrate_create_tables,
+}];
 
-        // 3. 创建索引 (针对排行榜查询优化)
-        // 索引1: WHERE group_id GROUP BY user_id
+fn migrate_create_tables(db: &DatabaseTransaction) -> BoxFuture<'_, Result<(), DbErr>> {
+    Box::pin(async move {
+        create_table(db, state_entity::Entity).await?;
+        create_table(db, record_entity::Entity).await?;
+
+        // 排行榜查询优化: WHERE group_id GROUP BY user_id
         let idx_group_user = sea_orm::sea_query::Index::create()
             .name("idx_ciyi_win_record_group_user")
             .table(record_entity::Entity)
@@ -53,13 +45,7 @@ pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
             .col(record_entity::Column::UserId)
             .if_not_exists()
             .to_owned();
-
-        if let Err(e) = db.execute(builder.build(&idx_group_user)).await {
-            crate::warn!(target: "Plugin/CiYi", "Init index error: {}", e);
-        }
-
-        // crate::info!(target: "Plugin/CiYi", "词意游戏初始化完成 (SQL)");
-        Ok(())
+        create_index(db, idx_group_user).await
     })
 }
 
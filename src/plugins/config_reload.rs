@@ -0,0 +1,134 @@
+use crate::adapters::onebot::{LockedWriter, send_msg};
+use crate::command::match_command;
+use crate::config::build_config;
+use crate::event::Context;
+use crate::message::Message;
+use crate::plugins::{PluginError, get_config, reload_config, validate_as};
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+use toml::Value;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Config {
+    enabled: bool,
+    /// 是否监视配置文件变化并自动重载
+    #[serde(default = "default_true")]
+    watch: bool,
+    /// 检查文件修改时间的间隔 (秒)
+    #[serde(default = "default_interval")]
+    interval_seconds: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_interval() -> u64 {
+    3
+}
+
+pub fn default_config() -> Value {
+    build_config(Config {
+        enabled: true,
+        watch: true,
+        interval_seconds: default_interval(),
+    })
+}
+
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
+async fn modified_time(path: &str) -> Option<SystemTime> {
+    tokio::fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// 执行重载并输出日志，返回给管理员的结果描述
+/// force: 手动重载时通知所有插件，使其重新读取 data 目录下的数据文件
+async fn do_reload(ctx: &Context, force: bool) -> String {
+    if !force {
+        // 文件监视只覆盖 config.toml
+        info!(
+            target: "Config",
+            "自动重载不会重新读取插件数据文件 (如 oai/config.json、shindan/shindans.toml)，修改后请发送“重载配置”"
+        );
+    }
+    match reload_config(ctx, force).await {
+        Ok(changed) if changed.is_empty() => {
+            info!(target: "Config", "配置已重载 (插件配置无变化)");
+            "✅ 配置已重载，插件配置无变化".to_string()
+        }
+        Ok(changed) => {
+            info!(target: "Config", "配置已重载，变更的插件: {}", changed.join(", "));
+            format!("✅ 配置已重载\n变更的插件: {}", changed.join(", "))
+        }
+        Err(e) => {
+            error!(target: "Config", "配置重载失败，继续使用旧配置: {}", e);
+            format!("❌ 配置重载失败，旧配置保持生效\n{}", e)
+        }
+    }
+}
+
+pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
+    Box::pin(async move {
+        let config: Config = get_config(&ctx, "config_reload")
+            .unwrap_or_else(|| Deserialize::deserialize(default_config()).unwrap());
+
+        if !config.watch {
+            return Ok(());
+        }
+
+        let interval = Duration::from_secs(config.interval_seconds.max(1));
+        let mut last_modified = modified_time(&ctx.config_path).await;
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                let current = modified_time(&ctx.config_path).await;
+                if current.is_none() || current == last_modified {
+                    continue;
+                }
+                last_modified = current;
+
+                debug!(target: "Config", "检测到配置文件变化，正在重载...");
+                do_reload(&ctx, false).await;
+            }
+        });
+
+        info!(target: "Config", "正在监视配置文件: {}", ctx.config_path);
+        Ok(())
+    })
+}
+
+pub fn handle(
+    ctx: Context,
+    writer: LockedWriter,
+) -> BoxFuture<'static, Result<Option<Context>, PluginError>> {
+    Box::pin(async move {
+        if match_command(&ctx, "重载配置").is_none() {
+            return Ok(Some(ctx));
+        }
+
+        let msg = match ctx.as_message() {
+            Some(m) => m,
+            None => return Ok(Some(ctx)),
+        };
+        let group_id = msg.group_id();
+        let user_id = msg.user_id();
+        let message_id = msg.message_id();
+
+        let is_superuser = ctx.config.read().unwrap().superusers.contains(&user_id);
+        if !is_superuser {
+            return Ok(Some(ctx));
+        }
+
+        let result = do_reload(&ctx, true).await;
+        let reply = Message::new().reply(message_id).text(result);
+        send_msg(&ctx, writer, group_id, Some(user_id), reply).await?;
+
+        Ok(None)
+    })
+}
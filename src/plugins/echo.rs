@@ -2,7 +2,7 @@ use crate::adapters::onebot::{LockedWriter, send_msg};
 use crate::command::match_command;
 use crate::config::build_config;
 use crate::event::Context;
-use crate::plugins::PluginError;
+use crate::plugins::{PluginError, validate_as};
 use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use toml::Value;
@@ -16,6 +16,11 @@ pub fn default_config() -> Value {
     build_config(Config { enabled: true })
 }
 
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
 pub fn handle(
     ctx: Context,
     writer: LockedWriter,
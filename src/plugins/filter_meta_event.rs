@@ -1,7 +1,7 @@
 use crate::adapters::onebot::LockedWriter;
 use crate::config::build_config;
 use crate::event::Context;
-use crate::plugins::PluginError;
+use crate::plugins::{PluginError, validate_as};
 use futures_util::future::BoxFuture;
 use serde::Serialize;
 use toml::Value;
@@ -15,6 +15,11 @@ pub fn default_config() -> Value {
     build_config(FilterConfig { enabled: true })
 }
 
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<FilterConfig>(value)
+}
+
 pub fn handle(
     ctx: Context,
     _writer: LockedWriter,
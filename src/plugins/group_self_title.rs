@@ -3,7 +3,7 @@ use crate::adapters::onebot::api;
 use crate::command::match_command;
 use crate::config::build_config;
 use crate::event::{Context, EventType};
-use crate::plugins::PluginError;
+use crate::plugins::{PluginError, validate_as};
 use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use simd_json::derived::{ValueObjectAccess, ValueObjectAccessAsScalar};
@@ -18,6 +18,11 @@ pub fn default_config() -> Value {
     build_config(Config { enabled: true })
 }
 
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
 pub fn handle(
     ctx: Context,
     writer: LockedWriter,
@@ -38,7 +43,7 @@ pub fn handle(
             let user_id = msg.user_id();
 
             // 2. 获取 Bot 自身的 ID (self_id)
-            let self_id = if let EventType::Onebot(ev) = &ctx.event {
+            let self_id = if let EventType::Onebot(ev) = &*ctx.event {
                 ev.get_i64("self_id")
                     .or_else(|| ev.get_u64("self_id").map(|v| v as i64))
                     .unwrap_or(0)
@@ -0,0 +1,225 @@
+use crate::adapters::onebot::{LockedWriter, send_msg};
+use crate::config::build_config;
+use crate::event::Context;
+use crate::message::Message;
+use crate::plugins::oai::utils::render_md;
+use crate::plugins::{PluginError, get_config, validate_as};
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::{Json, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::routing::post;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use subtle::ConstantTimeEq;
+use toml::Value;
+
+// ================= 配置定义 =================
+
+#[derive(Serialize, Deserialize, Clone)]
+struct HttpApiConfig {
+    enabled: bool,
+    /// 监听地址，建议仅绑定本机
+    #[serde(default = "default_listen")]
+    listen: String,
+    /// 鉴权令牌 (Authorization: Bearer <token>)，为空时拒绝启动
+    #[serde(default)]
+    token: String,
+}
+
+fn default_listen() -> String {
+    "127.0.0.1:5700".to_string()
+}
+
+pub fn default_config() -> Value {
+    build_config(HttpApiConfig {
+        enabled: false,
+        listen: default_listen(),
+        token: String::new(),
+    })
+}
+
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<HttpApiConfig>(value)
+}
+
+// ================= Bot 注册表 =================
+
+/// 已连接的 Bot (login_user.id -> 上下文与写入端)
+/// 连接断开时由 on_disconnected 移除，重连后 on_connected 再次登记新的 Writer
+static BOTS: OnceLock<RwLock<HashMap<String, (Context, LockedWriter)>>> = OnceLock::new();
+
+fn bots() -> &'static RwLock<HashMap<String, (Context, LockedWriter)>> {
+    BOTS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// 选择目标 Bot：指定 bot_id 时精确匹配，否则取任意一个在线 Bot
+fn pick_bot(bot_id: Option<&str>) -> Option<(Context, LockedWriter)> {
+    let guard = bots().read().unwrap();
+    match bot_id {
+        Some(id) => guard.get(id).cloned(),
+        None => guard.values().next().cloned(),
+    }
+}
+
+// ================= 请求定义 =================
+
+#[derive(Deserialize)]
+struct SendRequest {
+    #[serde(default)]
+    bot_id: Option<String>,
+    #[serde(default)]
+    group_id: Option<i64>,
+    #[serde(default)]
+    user_id: Option<i64>,
+    /// 字符串，或 OneBot 消息段数组 (与 message::Message 结构一致)
+    message: serde_json::Value,
+    /// 字符串消息的格式: "text" (默认) 或 "markdown" (渲染为图片)
+    #[serde(default)]
+    format: Option<String>,
+}
+
+type ApiResponse = (StatusCode, Json<serde_json::Value>);
+
+fn reply(status: StatusCode, msg: &str) -> ApiResponse {
+    let ok = status.is_success();
+    (
+        status,
+        Json(serde_json::json!({
+            "status": if ok { "ok" } else { "failed" },
+            "message": msg,
+        })),
+    )
+}
+
+// ================= 生命周期 =================
+
+pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
+    Box::pin(async move {
+        let config: HttpApiConfig = get_config(&ctx, "http_api")
+            .unwrap_or_else(|| serde::Deserialize::deserialize(default_config()).unwrap());
+
+        if config.token.trim().is_empty() {
+            return Err("未配置 token，出于安全考虑不启动 HTTP 推送接口".into());
+        }
+
+        let listener = tokio::net::TcpListener::bind(&config.listen).await?;
+        info!(target: "Plugin/HttpApi", "HTTP 推送接口已监听: http://{}/send", config.listen);
+
+        let app = Router::new()
+            .route("/send", post(handle_send))
+            .with_state(ctx);
+
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                error!(target: "Plugin/HttpApi", "HTTP 服务异常退出: {}", e);
+            }
+        });
+
+        Ok(())
+    })
+}
+
+pub fn on_connected(
+    ctx: Context,
+    writer: LockedWriter,
+) -> BoxFuture<'static, Result<Option<Context>, PluginError>> {
+    Box::pin(async move {
+        let bot_id = ctx.bot.login_user.id.clone();
+        bots().write().unwrap().insert(bot_id.clone(), (ctx.clone(), writer));
+        info!(target: "Plugin/HttpApi", "Bot [{}] 已注册到推送接口", bot_id);
+        Ok(Some(ctx))
+    })
+}
+
+pub fn on_disconnected(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
+    Box::pin(async move {
+        let bot_id = &ctx.bot.login_user.id;
+        if bots().write().unwrap().remove(bot_id).is_some() {
+            info!(target: "Plugin/HttpApi", "Bot [{}] 已断开，从推送接口移除", bot_id);
+        }
+        Ok(())
+    })
+}
+
+pub fn handle(
+    ctx: Context,
+    _writer: LockedWriter,
+) -> BoxFuture<'static, Result<Option<Context>, PluginError>> {
+    Box::pin(async move { Ok(Some(ctx)) })
+}
+
+// ================= HTTP 处理 =================
+
+async fn handle_send(State(ctx): State<Context>, headers: HeaderMap, body: Bytes) -> ApiResponse {
+    // 1. 鉴权 (每次读取最新配置)，通过后才解析请求体
+    let token = get_config::<HttpApiConfig>(&ctx, "http_api")
+        .map(|c| c.token)
+        .unwrap_or_default();
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or("");
+    // 常量时间比较，避免通过响应耗时逐字节猜测令牌
+    if token.is_empty() || !bool::from(provided.as_bytes().ct_eq(token.as_bytes())) {
+        return reply(StatusCode::UNAUTHORIZED, "鉴权失败");
+    }
+
+    let req: SendRequest = match serde_json::from_slice(&body) {
+        Ok(req) => req,
+        Err(e) => return reply(StatusCode::BAD_REQUEST, &format!("请求格式错误: {}", e)),
+    };
+
+    if req.group_id.is_none() && req.user_id.is_none() {
+        return reply(StatusCode::BAD_REQUEST, "group_id 与 user_id 至少指定一个");
+    }
+
+    // 2. 构造消息
+    let message = match req.message {
+        serde_json::Value::String(s) => match req.format.as_deref() {
+            Some("markdown") => match render_md(&s, "").await {
+                Ok(b64) => Message::new().image(format!("base64://{}", b64)),
+                Err(e) => {
+                    warn!(target: "Plugin/HttpApi", "Markdown 渲染失败: {}", e);
+                    return reply(StatusCode::INTERNAL_SERVER_ERROR, "Markdown 渲染失败");
+                }
+            },
+            _ => Message::from(s),
+        },
+        other => match serde_json::from_value::<Message>(other) {
+            Ok(m) => m,
+            Err(e) => {
+                return reply(StatusCode::BAD_REQUEST, &format!("message 格式错误: {}", e));
+            }
+        },
+    };
+
+    if message.0.is_empty() {
+        return reply(StatusCode::BAD_REQUEST, "消息内容为空");
+    }
+
+    // 3. 选择 Bot 并走正常的发送流水线 (BeforeSend)
+    let (bot_ctx, writer) = match pick_bot(req.bot_id.as_deref()) {
+        Some(b) => b,
+        None => return reply(StatusCode::SERVICE_UNAVAILABLE, "没有可用的 Bot"),
+    };
+
+    match send_msg(&bot_ctx, writer, req.group_id, req.user_id, message).await {
+        Ok(()) => {
+            info!(
+                target: "Plugin/HttpApi",
+                "已推送消息 -> Group({:?}) User({:?})",
+                req.group_id, req.user_id
+            );
+            reply(StatusCode::OK, "已发送")
+        }
+        Err(e) => {
+            error!(target: "Plugin/HttpApi", "推送失败: {}", e);
+            reply(StatusCode::INTERNAL_SERVER_ERROR, &format!("发送失败: {}", e))
+        }
+    }
+}
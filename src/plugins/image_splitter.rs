@@ -3,7 +3,7 @@ use crate::command::match_command;
 use crate::config::build_config;
 use crate::event::Context;
 use crate::message::Message;
-use crate::plugins::{PluginError, get_config};
+use crate::plugins::{PluginError, get_config, validate_as};
 use futures_util::future::BoxFuture;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -33,6 +33,11 @@ pub fn default_config() -> Value {
     })
 }
 
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
 // ================= 正则与工具 =================
 
 static ARGS_REGEX: OnceLock<Regex> = OnceLock::new();
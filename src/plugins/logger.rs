@@ -1,7 +1,7 @@
 use crate::adapters::onebot::LockedWriter;
 use crate::config::build_config;
 use crate::event::{Context, EventType};
-use crate::plugins::{PluginError, get_config};
+use crate::plugins::{PluginError, get_config, validate_as};
 use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use simd_json::OwnedValue;
@@ -23,6 +23,11 @@ pub fn default_config() -> Value {
     })
 }
 
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<LoggerConfig>(value)
+}
+
 pub fn handle(
     ctx: Context,
     _writer: LockedWriter,
@@ -34,7 +39,7 @@ pub fn handle(
             debug: false,
         });
 
-        match &ctx.event {
+        match &*ctx.event {
             EventType::Onebot(ev) => {
                 if config.debug {
                     debug!(target: "Logger", "ev: {:?}", ev);
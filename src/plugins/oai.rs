@@ -1,8 +1,10 @@
 use crate::adapters::onebot::LockedWriter;
 use crate::config::build_config;
+use crate::dispatcher::detach;
 use crate::event::Context;
-use crate::plugins::{PluginError, get_data_dir};
+use crate::plugins::{PluginError, get_data_dir, validate_as};
 use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
 use simd_json::derived::{ValueObjectAccess, ValueObjectAccessAsArray, ValueObjectAccessAsScalar};
 
 use std::sync::Arc;
@@ -16,10 +18,19 @@ pub mod utils;
 
 use data::MANAGER;
 
+/// 插件配置表仅包含开关，模型与 API 等配置位于 data/oai/config.json
+#[derive(Serialize, Deserialize)]
+struct Config {
+    enabled: bool,
+}
+
 pub fn default_config() -> Value {
-    build_config(serde_json::json!({
-        "enabled": true
-    }))
+    build_config(Config { enabled: true })
+}
+
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
 }
 
 pub fn init(_ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
@@ -44,9 +55,21 @@ pub fn init(_ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
     })
 }
 
+/// 配置重载时重新读取 data/oai/config.json
+/// 仅修改该文件时，通过“重载配置”命令触发
+pub fn on_config_changed(_ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
+    Box::pin(async move {
+        if let Some(mgr) = MANAGER.get() {
+            mgr.reload().await;
+            info!(target: "OAI", "已重新加载配置");
+        }
+        Ok(())
+    })
+}
+
 // 提取纯文本内容，自动忽略头部的 At 和 Reply 消息段
 fn extract_clean_text(ctx: &Context) -> Option<String> {
-    let event = match &ctx.event {
+    let event = match &*ctx.event {
         crate::event::EventType::Onebot(e) => e,
         _ => return None,
     };
@@ -137,19 +160,22 @@ pub fn handle(
 
         // 4. 智能体指令/对话解析
         if let Some(cmd) = parser::parse_agent_cmd(&raw_text, &agents) {
-            let (quote, imgs) = utils::get_full_content(&ctx, &writer, Some(&cmd.agent)).await;
-
-            // 拼接提示词：引用 + 用户输入参数
-            let prompt = if matches!(
-                cmd.action,
-                parser::Action::Chat | parser::Action::Regenerate
-            ) {
-                format!("{}{}", quote, cmd.args).trim().to_string()
-            } else {
-                cmd.args.clone()
-            };
-
-            logic::execute(cmd, prompt, imgs, &ctx, &writer, mgr).await;
+            // 对话可能持续数十秒，分离到后台执行，不阻塞同频道的后续消息
+            detach("oai", async move {
+                let (quote, imgs) = utils::get_full_content(&ctx, &writer, Some(&cmd.agent)).await;
+
+                // 拼接提示词：引用 + 用户输入参数
+                let prompt = if matches!(
+                    cmd.action,
+                    parser::Action::Chat | parser::Action::Regenerate
+                ) {
+                    format!("{}{}", quote, cmd.args).trim().to_string()
+                } else {
+                    cmd.args.clone()
+                };
+
+                logic::execute(cmd, prompt, imgs, &ctx, &writer, mgr).await;
+            });
             return Ok(None);
         }
 
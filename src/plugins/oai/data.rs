@@ -1,7 +1,8 @@
 use super::types::{Config, GeneratingState};
+use crate::config::{resolve_json_secrets, restore_json_refs};
 use async_openai::Client;
 use async_openai::config::OpenAIConfig;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, OnceLock};
 
 use tokio::sync::RwLock;
@@ -9,6 +10,9 @@ use tokio::sync::RwLock;
 // 全局单例管理器
 pub static MANAGER: OnceLock<Arc<Manager>> = OnceLock::new();
 
+/// 允许使用密钥引用的凭据字段
+const SECRET_FIELDS: &[&str] = &["api_key"];
+
 pub struct Manager {
     pub config: RwLock<Config>,
     pub generating: RwLock<GeneratingState>,
@@ -25,14 +29,7 @@ impl Manager {
             ..Default::default()
         };
 
-        let config = if path.exists() {
-            match std::fs::read_to_string(&path) {
-                Ok(s) => serde_json::from_str(&s).unwrap_or(default),
-                Err(_) => default,
-            }
-        } else {
-            default
-        };
+        let config = Self::load(&path).unwrap_or(default);
 
         Self {
             config: RwLock::new(config),
@@ -41,13 +38,57 @@ impl Manager {
         }
     }
 
+    /// 读取配置文件，api_key 中的 ${VAR} / file: 密钥引用会被解析
+    fn load(path: &Path) -> Option<Config> {
+        let s = std::fs::read_to_string(path).ok()?;
+        let parsed = serde_json::from_str::<serde_json::Value>(&s).and_then(|mut v| {
+            resolve_json_secrets(&mut v, SECRET_FIELDS);
+            serde_json::from_value(v)
+        });
+        match parsed {
+            Ok(cfg) => Some(cfg),
+            Err(e) => {
+                warn!(target: "OAI", "解析 {:?} 失败: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// 从磁盘重新读取配置，文件缺失或格式错误时保留当前配置
+    pub async fn reload(&self) {
+        if let Some(cfg) = Self::load(&self.path) {
+            *self.config.write().await = cfg;
+        }
+    }
+
+    /// 保存配置，文件中原有的密钥引用保持不变，不写入解析后的密钥
     pub fn save(&self, cfg: &Config) {
-        if let Ok(s) = serde_json::to_string_pretty(cfg) {
+        let Ok(mut value) = serde_json::to_value(cfg) else {
+            return;
+        };
+        if let Some(old) = std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        {
+            restore_json_refs(&mut value, &old, SECRET_FIELDS);
+        }
+
+        if let Ok(s) = serde_json::to_string_pretty(&value) {
             // 使用 std::fs 写文件，虽然是阻塞操作，但保存配置频率不高
             let _ = std::fs::write(&self.path, s);
         }
     }
 
+    /// 删除用户在所有智能体中的对话记录并保存，返回删除的消息数
+    pub async fn purge_user(&self, uid: &str) -> usize {
+        let mut c = self.config.write().await;
+        let removed = c.agents.iter_mut().map(|a| a.purge_user(uid)).sum();
+        if removed > 0 {
+            self.save(&c);
+        }
+        removed
+    }
+
     pub async fn fetch_models(&self) -> anyhow::Result<Vec<String>> {
         let (base, key) = {
             let c = self.config.read().await;
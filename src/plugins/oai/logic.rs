@@ -175,14 +175,14 @@ async fn chat(
             if hist.last().map(|m| m.role == "user").unwrap_or(false) {
                 hist.pop();
             }
-            hist.push(ChatMessage::new("user", prompt, imgs.clone()));
+            hist.push(ChatMessage::new("user", prompt, imgs.clone()).with_sender(&uid));
         }
     } else {
         if prompt.is_empty() && imgs.is_empty() {
             reply_text(ctx, writer, &event, "💬 请输入内容").await;
             return;
         }
-        hist.push(ChatMessage::new("user", prompt, imgs.clone()));
+        hist.push(ChatMessage::new("user", prompt, imgs.clone()).with_sender(&uid));
     }
 
     let gen_id = if temp_mode {
@@ -9,6 +9,9 @@ pub struct ChatMessage {
     pub images: Vec<String>,
     #[serde(default)]
     pub timestamp: i64,
+    /// 发送者 QQ 号 (仅 user 消息)，用于删除个人数据时定位公共对话中的发言
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sender: String,
 }
 
 impl ChatMessage {
@@ -18,8 +21,14 @@ impl ChatMessage {
             content: content.to_string(),
             images,
             timestamp: chrono::Local::now().timestamp(),
+            sender: String::new(),
         }
     }
+
+    pub fn with_sender(mut self, uid: &str) -> Self {
+        self.sender = uid.to_string();
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +91,24 @@ impl Agent {
         }
     }
 
+    /// 删除用户的私聊记录及其在公共对话中的发言 (连同紧随其后的回复)，返回删除的消息数
+    pub fn purge_user(&mut self, uid: &str) -> usize {
+        let private = self.private_histories.remove(uid).map_or(0, |h| h.len());
+
+        let before = self.public_history.len();
+        let mut dropping = false;
+        self.public_history.retain(|m| match m.role.as_str() {
+            "user" => {
+                dropping = m.sender == uid;
+                !dropping
+            }
+            "assistant" => !std::mem::take(&mut dropping),
+            _ => true,
+        });
+
+        private + before - self.public_history.len()
+    }
+
     pub fn delete_at(&mut self, private: bool, uid: &str, indices: &[usize]) -> Vec<usize> {
         let h = self.history_mut(private, uid);
         let mut deleted = Vec::new();
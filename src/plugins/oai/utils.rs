@@ -216,7 +216,7 @@ pub async fn get_full_content(
     let mut quote_text = String::new();
     let mut imgs = Vec::new();
 
-    let onebot_event = match &ctx.event {
+    let onebot_event = match &*ctx.event {
         crate::event::EventType::Onebot(e) => e,
         _ => return (quote_text, imgs),
     };
@@ -1,15 +1,16 @@
 use crate::adapters::onebot::{LockedWriter, send_msg};
 use crate::command::match_command;
 use crate::config::build_config;
+use crate::db::migrations::{Migration, create_table};
 use crate::event::Context;
 use crate::message::Message;
-use crate::plugins::PluginError;
+use crate::plugins::{PluginError, validate_as};
 use futures_util::future::BoxFuture;
-use sea_orm::{ActiveModelTrait, ConnectionTrait, EntityTrait, PaginatorTrait, Schema, Set};
+use sea_orm::{ActiveModelTrait, DatabaseTransaction, DbErr, EntityTrait, PaginatorTrait, Set};
 use serde::{Deserialize, Serialize};
 use toml::Value;
 
-mod entity {
+pub mod entity {
     use sea_orm::entity::prelude::*;
 
     #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
@@ -45,23 +46,19 @@ pub fn default_config() -> Value {
     build_config(PingConfig { enabled: true })
 }
 
-pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
-    Box::pin(async move {
-        let db = &ctx.db;
-        let builder = db.get_database_backend();
-        let schema = Schema::new(builder);
-
-        let mut create_table_stmt = schema.create_table_from_entity(PingStats);
-        create_table_stmt.if_not_exists();
-
-        let stmt = builder.build(&create_table_stmt);
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<PingConfig>(value)
+}
 
-        db.execute(stmt)
-            .await
-            .map_err(|e| format!("PingPong Plugin Init DB Error: {}", e))?;
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    name: "create_plugin_ping_stats",
+    up: migrate_create_stats,
+}];
 
-        Ok(())
-    })
+fn migrate_create_stats(db: &DatabaseTransaction) -> BoxFuture<'_, Result<(), DbErr>> {
+    Box::pin(create_table(db, PingStats))
 }
 
 pub fn handle(
@@ -0,0 +1,175 @@
+use crate::adapters::onebot::{LockedWriter, send_msg};
+use crate::command::match_command;
+use crate::config::{build_config, data_dir};
+use crate::dispatcher::detach;
+use crate::event::{Context, MessageEvent};
+use crate::message::Message;
+use crate::plugins::recorder::privacy;
+use crate::plugins::{PluginError, ciyi, get_config, oai, ping_pong, shindan, validate_as};
+use futures_util::future::BoxFuture;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use toml::Value;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Config {
+    enabled: bool,
+    /// 删除数据时等待确认的秒数
+    #[serde(default = "default_confirm_timeout")]
+    confirm_timeout_secs: u64,
+}
+
+fn default_confirm_timeout() -> u64 {
+    60
+}
+
+pub fn default_config() -> Value {
+    build_config(Config {
+        enabled: true,
+        confirm_timeout_secs: default_confirm_timeout(),
+    })
+}
+
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
+pub fn handle(
+    ctx: Context,
+    writer: LockedWriter,
+) -> BoxFuture<'static, Result<Option<Context>, PluginError>> {
+    Box::pin(async move {
+        let opt_out = if match_command(&ctx, "不要记录我").is_some() {
+            Some(true)
+        } else if match_command(&ctx, "恢复记录我").is_some() {
+            Some(false)
+        } else {
+            None
+        };
+
+        if let Some(opt_out) = opt_out {
+            let Some(msg) = ctx.as_message() else {
+                return Ok(Some(ctx));
+            };
+            let group_id = msg.group_id();
+            let user_id = msg.user_id();
+            let message_id = msg.message_id();
+
+            let store = ctx.store(privacy::NAMESPACE);
+            let changed = privacy::set_opt_out(&store, user_id, opt_out).await?;
+            info!(target: "Plugin/Privacy", "用户 {} {}记录", user_id, if opt_out { "退出" } else { "恢复" });
+
+            let text = match (opt_out, changed) {
+                (true, true) => {
+                    "已停止记录你的消息，你也不会再出现在排行、词云与关系图中。\n已有的记录仍会保留，如需删除请发送“删除我的数据”。"
+                }
+                (true, false) => "你已经处于不记录状态。",
+                (false, true) => "已恢复记录你的消息。",
+                (false, false) => "你的消息本来就在记录中。",
+            };
+            let reply = Message::new().reply(message_id).text(text);
+            send_msg(&ctx, writer, group_id, Some(user_id), reply).await?;
+            return Ok(None);
+        }
+
+        if match_command(&ctx, "删除我的数据").is_none() {
+            return Ok(Some(ctx));
+        }
+
+        let Some(msg) = ctx.as_message() else {
+            return Ok(Some(ctx));
+        };
+        let group_id = msg.group_id();
+        let user_id = msg.user_id();
+        let message_id = msg.message_id();
+
+        let config: Config = get_config(&ctx, "privacy")
+            .unwrap_or_else(|| Deserialize::deserialize(default_config()).unwrap());
+
+        let prompt = Message::new().reply(message_id).text(format!(
+            "此操作将删除你在所有聊天中的消息记录、统计数据与 AI 对话记录，且无法撤销。“不要记录我”的设置会保留。\n注意：已生成的数据库备份仍保留这些数据，直到按保留数量轮换删除；过期消息的归档文件不会被修改。\n请在 {} 秒内回复“确认”以继续。",
+            config.confirm_timeout_secs
+        ));
+        send_msg(&ctx, writer.clone(), group_id, Some(user_id), prompt).await?;
+
+        // 等待确认期间不占用插件链
+        let task_ctx = ctx.clone();
+        detach("privacy_delete", async move {
+            let timeout = Duration::from_secs(config.confirm_timeout_secs);
+            let confirmed = task_ctx
+                .wait_input(group_id, Some(user_id), timeout)
+                .await
+                .is_some_and(|ev| MessageEvent(&ev).text().trim() == "确认");
+
+            let text = if !confirmed {
+                "已取消删除。".to_string()
+            } else {
+                match purge(&task_ctx.db, user_id).await {
+                    Ok(count) => {
+                        info!(target: "Plugin/Privacy", "已删除用户 {} 的数据 ({} 条消息)", user_id, count);
+                        format!(
+                            "已删除你的 {} 条消息记录及相关统计数据与 AI 对话记录。\n数据库备份中的副本会随备份轮换清除，归档文件中的副本需联系管理员处理。\n如不希望继续被记录，请发送“不要记录我”。",
+                            count
+                        )
+                    }
+                    Err(e) => {
+                        error!(target: "Plugin/Privacy", "删除用户 {} 的数据失败: {}", user_id, e);
+                        format!("删除失败: {}", e)
+                    }
+                }
+            };
+
+            let reply = Message::new().text(text);
+            if let Err(e) = send_msg(&task_ctx, writer, group_id, Some(user_id), reply).await {
+                error!(target: "Plugin/Privacy", "发送删除结果失败: {}", e);
+            }
+        });
+
+        Ok(None)
+    })
+}
+
+/// 删除用户在消息记录、各插件表与 AI 对话记录中的数据，返回删除的消息数
+/// 保留 plugin_kv 中的退出记录设置；不涉及 backup 插件生成的数据库快照与 <数据目录>/archive 下的过期消息归档，提示文案中需如实告知
+async fn purge(db: &DatabaseConnection, user_id: i64) -> Result<u64, DbErr> {
+    let count = privacy::purge_user(db, user_id).await?;
+
+    // 其他插件的统计表，单表失败只记录警告，不影响其余数据的删除
+    let results = [
+        ping_pong::entity::Entity::delete_many()
+            .filter(ping_pong::entity::Column::UserId.eq(user_id))
+            .exec(db)
+            .await,
+        shindan::entity::user_stats::Entity::delete_many()
+            .filter(shindan::entity::user_stats::Column::UserId.eq(user_id))
+            .exec(db)
+            .await,
+        ciyi::entity::record::Entity::delete_many()
+            .filter(ciyi::entity::record::Column::UserId.eq(user_id))
+            .exec(db)
+            .await,
+    ];
+    for res in results {
+        if let Err(e) = res {
+            warn!(target: "Plugin/Privacy", "删除插件数据失败: {}", e);
+        }
+    }
+
+    // oai 的对话记录保存在 data/oai/config.json，插件未启用时直接读写该文件
+    let uid = user_id.to_string();
+    let removed = match oai::data::MANAGER.get() {
+        Some(mgr) => mgr.purge_user(&uid).await,
+        None => {
+            oai::data::Manager::new(data_dir().join("oai"))
+                .purge_user(&uid)
+                .await
+        }
+    };
+    if removed > 0 {
+        info!(target: "Plugin/Privacy", "已删除用户 {} 的 {} 条 AI 对话记录", user_id, removed);
+    }
+
+    Ok(count)
+}
@@ -1,11 +1,14 @@
 use crate::adapters::onebot::LockedWriter;
 use crate::config::build_config;
+use crate::db::migrations::{Migration, create_index, create_table};
 use crate::event::{Context, EventType};
-use crate::plugins::{PluginError, get_config};
+use crate::plugins::{PluginError, get_config, validate_as};
 use chrono::{Datelike, Duration, Local, TimeZone, Timelike};
 use futures_util::future::BoxFuture;
 use jieba_rs::Jieba;
-use sea_orm::{ActiveModelTrait, ActiveValue, ConnectionTrait, Schema, Set, Statement};
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter, Set,
+};
 use serde::{Deserialize, Serialize};
 use simd_json::OwnedValue;
 use simd_json::base::{ValueAsArray, ValueAsScalar};
@@ -13,6 +16,12 @@ use simd_json::derived::{ValueObjectAccess, ValueObjectAccessAsScalar};
 use std::sync::OnceLock;
 use toml::Value;
 
+pub mod fts;
+pub mod interactions;
+pub mod privacy;
+pub mod queue;
+pub mod raw;
+
 pub mod entity {
     use sea_orm::entity::prelude::*;
 
@@ -66,6 +75,17 @@ pub mod entity {
     pub enum Relation {}
 
     impl ActiveModelBehavior for ActiveModel {}
+
+    impl Model {
+        /// 展示用的名称：优先群名片，其次昵称
+        pub fn display_name(&self) -> &str {
+            if self.sender_nick.is_empty() {
+                &self.user_name
+            } else {
+                &self.sender_nick
+            }
+        }
+    }
 }
 
 use entity::ActiveModel as RecordActiveModel;
@@ -86,12 +106,39 @@ struct RecorderConfig {
     // 数据保留天数，默认 180 天
     #[serde(default = "default_retention_days")]
     retention_days: i64,
+    // 清理前将过期记录按月归档到 <数据目录>/archive (gzip 压缩的 JSON Lines)
+    #[serde(default)]
+    archive: bool,
+    // 额外保存压缩后的原始消息段、message_id 与图片哈希，用于还原原消息
+    #[serde(default)]
+    store_raw: bool,
+    // 后台写入队列容量，队列满时消息处理会等待写入
+    #[serde(default = "default_queue_capacity")]
+    queue_capacity: usize,
+    // 每个事务最多写入的记录数
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    // 未攒满一批时，最早一条记录最多等待的毫秒数
+    #[serde(default = "default_flush_interval_ms")]
+    flush_interval_ms: u64,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_queue_capacity() -> usize {
+    4096
+}
+
+fn default_batch_size() -> usize {
+    200
+}
+
+fn default_flush_interval_ms() -> u64 {
+    500
+}
+
 fn default_retention_days() -> i64 {
     180
 }
@@ -101,26 +148,49 @@ pub fn default_config() -> Value {
         enabled: true,
         record_self: true,
         retention_days: 180,
+        archive: false,
+        store_raw: false,
+        queue_capacity: default_queue_capacity(),
+        batch_size: default_batch_size(),
+        flush_interval_ms: default_flush_interval_ms(),
     })
 }
 
-pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
-    Box::pin(async move {
-        let db = &ctx.db;
-        let builder = db.get_database_backend();
-        let schema = Schema::new(builder);
-
-        // 1. 创建表
-        let mut create_table_stmt = schema.create_table_from_entity(RecordEntity);
-        create_table_stmt.if_not_exists();
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<RecorderConfig>(value)
+}
 
-        let stmt = builder.build(&create_table_stmt);
-        if let Err(e) = db.execute(stmt).await {
-            warn!(target: "Plugin/Recorder", "Init table error (ignore if exists): {}", e);
-        }
+// ================= 数据库迁移 =================
+
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_message_records",
+        up: migrate_create_records,
+    },
+    Migration {
+        version: 2,
+        name: "create_message_interactions",
+        up: interactions::migrate_create_interactions,
+    },
+    Migration {
+        version: 3,
+        name: "create_message_records_fts",
+        up: fts::migrate_create_fts,
+    },
+    Migration {
+        version: 4,
+        name: "create_message_raw",
+        up: raw::migrate_create_raw,
+    },
+];
+
+fn migrate_create_records(db: &DatabaseTransaction) -> BoxFuture<'_, Result<(), DbErr>> {
+    Box::pin(async move {
+        create_table(db, RecordEntity).await?;
 
-        // 2. 创建索引
-        let indexes = vec![
+        let indexes = [
             sea_orm::sea_query::Index::create()
                 .name("idx_records_group_time")
                 .table(RecordEntity)
@@ -152,11 +222,32 @@ pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
         ];
 
         for idx in indexes {
-            let stmt = builder.build(&idx);
-            let _ = db.execute(stmt).await;
+            create_index(db, idx).await?;
         }
+        Ok(())
+    })
+}
 
-        // 3. 注册每日数据清理任务
+pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
+    Box::pin(async move {
+        // 启动后台批量写入任务
+        let config: RecorderConfig = get_config(&ctx, "recorder")
+            .unwrap_or_else(|| Deserialize::deserialize(default_config()).unwrap());
+        queue::start(
+            ctx.db.clone(),
+            config.queue_capacity,
+            config.batch_size,
+            std::time::Duration::from_millis(config.flush_interval_ms),
+        );
+
+        // 加载选择不被记录的用户
+        match privacy::load(&ctx.store(privacy::NAMESPACE)).await {
+            Ok(n) if n > 0 => info!(target: "Plugin/Recorder", "已加载 {} 位不记录的用户", n),
+            Ok(_) => {}
+            Err(e) => error!(target: "Plugin/Recorder", "加载不记录的用户列表失败: {}", e),
+        }
+
+        // 注册每日数据清理任务
         let scheduler = ctx.scheduler.clone();
         let db_clone = ctx.db.clone();
         let config_clone = ctx.config.clone();
@@ -165,12 +256,15 @@ pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
             let db = db_clone.clone();
             let cfg = config_clone.clone();
             async move {
-                let retention_days = {
+                let (retention_days, archive) = {
                     let guard = cfg.read().unwrap();
                     if let Some(v) = guard.plugins.get("recorder") {
-                        v.get("retention_days").and_then(|x| x.as_integer()).unwrap_or(180)
+                        (
+                            v.get("retention_days").and_then(|x| x.as_integer()).unwrap_or(180),
+                            v.get("archive").and_then(|x| x.as_bool()).unwrap_or(false),
+                        )
                     } else {
-                        180
+                        (180, false)
                     }
                 };
 
@@ -182,21 +276,45 @@ pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
                 let cutoff_time = Local::now() - Duration::days(retention_days);
                 let timestamp = cutoff_time.timestamp();
 
+                if archive {
+                    let dir = crate::config::data_dir().join("archive");
+                    match crate::db::backup::archive_records(&db, &dir, timestamp).await {
+                        Ok(n) => info!(target: "Plugin/Recorder", "已归档 {} 条过期消息记录到 {}", n, dir.display()),
+                        Err(e) => {
+                            // 归档失败时不删除数据，等待下次重试
+                            error!(target: "Plugin/Recorder", "归档过期数据失败，跳过本次清理: {}", e);
+                            return;
+                        }
+                    }
+                }
+
                 info!(target: "Plugin/Recorder", "开始清理 {} 天前的数据 (Time < {})...", retention_days, timestamp);
 
-                let delete_sql = format!("DELETE FROM message_records WHERE time < {}", timestamp);
-                let res = db.execute(Statement::from_string(sea_orm::DatabaseBackend::Sqlite, delete_sql)).await;
+                let res = RecordEntity::delete_many()
+                    .filter(entity::Column::Time.lt(timestamp))
+                    .exec(&db)
+                    .await;
 
                 match res {
                     Ok(exec_res) => {
                         let rows = exec_res.rows_affected();
+                        if let Err(e) = interactions::entity::Entity::delete_many()
+                            .filter(interactions::entity::Column::Time.lt(timestamp))
+                            .exec(&db)
+                            .await
+                        {
+                            warn!(target: "Plugin/Recorder", "清理过期互动关系失败: {}", e);
+                        }
+                        if let Err(e) = raw::delete_before(&db, timestamp).await {
+                            warn!(target: "Plugin/Recorder", "清理过期原始消息失败: {}", e);
+                        }
                         info!(target: "Plugin/Recorder", "已清理 {} 条过期消息记录。", rows);
                         if rows > 0 {
                             info!(target: "Plugin/Recorder", "正在整理数据库碎片 (VACUUM)...");
-                            if let Err(e) = db.execute(Statement::from_string(sea_orm::DatabaseBackend::Sqlite, "VACUUM;".to_owned())).await {
-                                warn!(target: "Plugin/Recorder", "VACUUM 执行失败: {}", e);
-                            } else {
-                                info!(target: "Plugin/Recorder", "数据库整理完成。");
+                            match crate::db::dialect::vacuum(&db).await {
+                                Ok(true) => info!(target: "Plugin/Recorder", "数据库整理完成。"),
+                                Ok(false) => {}
+                                Err(e) => warn!(target: "Plugin/Recorder", "VACUUM 执行失败: {}", e),
                             }
                         }
                     },
@@ -213,14 +331,11 @@ pub fn init(ctx: Context) -> BoxFuture<'static, Result<(), PluginError>> {
 
 pub fn handle(
     ctx: Context,
-    _writer: LockedWriter,
+    writer: LockedWriter,
 ) -> BoxFuture<'static, Result<Option<Context>, PluginError>> {
     Box::pin(async move {
-        let config: RecorderConfig = get_config(&ctx, "recorder").unwrap_or(RecorderConfig {
-            enabled: true,
-            record_self: true,
-            retention_days: 180,
-        });
+        let config: RecorderConfig = get_config(&ctx, "recorder")
+            .unwrap_or_else(|| Deserialize::deserialize(default_config()).unwrap());
 
         let mut record = RecordActiveModel {
             platform: Set("qq".to_string()),
@@ -231,8 +346,12 @@ pub fn handle(
         // 用于接收计算出的纯文本长度和待分词文本
         let mut text_len = 0;
         let mut raw_text_for_tokens = String::new();
+        let mut targets = interactions::Targets::default();
+        // 原始消息段与 message_id，仅在开启 store_raw 时保存
+        let mut raw_message: Option<OwnedValue> = None;
+        let mut message_id = None;
 
-        let should_insert = match &ctx.event {
+        let should_insert = match &*ctx.event {
             // === 接收消息 ===
             EventType::Onebot(ev) => {
                 let post_type = ev.get_str("post_type").unwrap_or("");
@@ -259,10 +378,21 @@ pub fn handle(
                 record.group_name = Set(group_name.to_string());
 
                 // 3. 用户信息
-                record.user_id = Set(ev
+                let user_id = ev
                     .get_i64("user_id")
                     .or_else(|| ev.get_u64("user_id").map(|v| v as i64))
-                    .unwrap_or(0));
+                    .unwrap_or(0);
+                if privacy::is_opted_out(user_id) {
+                    return Ok(Some(ctx));
+                }
+                record.user_id = Set(user_id);
+
+                message_id = ev
+                    .get_i64("message_id")
+                    .or_else(|| ev.get_u64("message_id").map(|v| v as i64));
+                if let Some(message_id) = message_id {
+                    interactions::remember_sender(message_id, user_id);
+                }
 
                 if let Some(sender) = ev.get("sender") {
                     let nick = sender.get_str("nickname").unwrap_or("");
@@ -280,8 +410,11 @@ pub fn handle(
 
                 // 4. 消息内容
                 let msg_val = ev.get("message");
+                if config.store_raw {
+                    raw_message = msg_val.cloned();
+                }
                 // 解析富文本，填充 content_rich 和获取待分词文本
-                let (len, raw) = parse_message_content(msg_val, &mut record);
+                let (len, raw) = parse_message_content(msg_val, &mut record, &mut targets);
                 text_len = len;
                 raw_text_for_tokens = raw;
 
@@ -330,8 +463,11 @@ pub fn handle(
                 record.role = Set("self".to_string());
 
                 let msg_val = packet.message();
+                if config.store_raw {
+                    raw_message = msg_val.cloned();
+                }
 
-                let (len, raw) = parse_message_content(msg_val, &mut record);
+                let (len, raw) = parse_message_content(msg_val, &mut record, &mut targets);
                 text_len = len;
                 raw_text_for_tokens = raw;
 
@@ -355,21 +491,17 @@ pub fn handle(
             // 计算长度 (仅统计文本消息的字符数)
             record.length = Set(text_len);
 
-            if !raw_text_for_tokens.is_empty() {
-                let tokens = tokio::task::spawn_blocking(move || {
-                    let jieba = get_jieba();
-                    jieba.cut(&raw_text_for_tokens, false).join(" ")
-                })
-                .await
-                .unwrap_or_default();
-                record.tokens = Set(tokens);
-            } else {
-                record.tokens = Set("".to_string());
-            }
-
-            if let Err(e) = record.insert(&ctx.db).await {
-                error!(target: "Plugin/Recorder", "消息记录失败: {}", e);
-            }
+            // 分词与写库交给后台写入任务，避免阻塞后续插件
+            queue::push(queue::Job {
+                record,
+                text: raw_text_for_tokens,
+                targets,
+                message_id,
+                raw_message,
+                ctx: ctx.clone(),
+                writer,
+            })
+            .await;
         }
 
         Ok(Some(ctx))
@@ -377,9 +509,11 @@ pub fn handle(
 }
 
 /// 解析消息段数组，提取富文本摘要、特征标记，并返回 (纯文本长度, 拼接后的纯文本)
+/// 同时收集回复与 @ 的目标，写入 targets
 fn parse_message_content(
     msg_val: Option<&OwnedValue>,
     record: &mut RecordActiveModel,
+    targets: &mut interactions::Targets,
 ) -> (i32, String) {
     let mut rich_text = String::new();
     // 存储分段的纯文本，用于最终拼接 tokens
@@ -436,6 +570,9 @@ fn parse_message_content(
                                     .or_else(|| d.get_u64("qq").map(|i| i.to_string()))
                             })
                             .unwrap_or_default();
+                        if let Ok(id) = qq.parse::<i64>() {
+                            targets.mentions.push(id);
+                        }
                         rich_text.push_str(&format!("[@{}]", qq));
                     }
                     "face" => {
@@ -487,6 +624,12 @@ fn parse_message_content(
                     }
                     "reply" => {
                         is_reply_flag = true;
+                        targets.reply_message_id = data.and_then(|d| {
+                            d.get_str("id")
+                                .and_then(|s| s.parse().ok())
+                                .or_else(|| d.get_i64("id"))
+                                .or_else(|| d.get_u64("id").map(|v| v as i64))
+                        });
                         rich_text.push_str("[回复]");
                     }
                     "json" => rich_text.push_str("[卡片]"),
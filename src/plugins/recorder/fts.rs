@@ -0,0 +1,83 @@
+use super::{entity, get_jieba};
+use futures_util::future::BoxFuture;
+use sea_orm::sea_query::{Expr, LikeExpr, SimpleExpr};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseTransaction, DbErr};
+
+/// message_records 的 FTS5 全文索引 (仅 SQLite)
+/// 以 message_records 为外部内容表，由触发器在插入 / 删除 / 更新时同步维护
+const CREATE_FTS: &[&str] = &[
+    "CREATE VIRTUAL TABLE IF NOT EXISTS message_records_fts USING fts5(\
+        content_rich, tokens, content='message_records', content_rowid='id')",
+    "CREATE TRIGGER IF NOT EXISTS message_records_fts_ai AFTER INSERT ON message_records BEGIN \
+        INSERT INTO message_records_fts(rowid, content_rich, tokens) \
+        VALUES (new.id, new.content_rich, new.tokens); \
+    END",
+    "CREATE TRIGGER IF NOT EXISTS message_records_fts_ad AFTER DELETE ON message_records BEGIN \
+        INSERT INTO message_records_fts(message_records_fts, rowid, content_rich, tokens) \
+        VALUES ('delete', old.id, old.content_rich, old.tokens); \
+    END",
+    "CREATE TRIGGER IF NOT EXISTS message_records_fts_au AFTER UPDATE ON message_records BEGIN \
+        INSERT INTO message_records_fts(message_records_fts, rowid, content_rich, tokens) \
+        VALUES ('delete', old.id, old.content_rich, old.tokens); \
+        INSERT INTO message_records_fts(rowid, content_rich, tokens) \
+        VALUES (new.id, new.content_rich, new.tokens); \
+    END",
+    // 为迁移前已有的记录建立索引
+    "INSERT INTO message_records_fts(message_records_fts) VALUES ('rebuild')",
+];
+
+pub(super) fn migrate_create_fts(db: &DatabaseTransaction) -> BoxFuture<'_, Result<(), DbErr>> {
+    Box::pin(async move {
+        // 其他数据库没有 FTS5，搜索时退化为 LIKE
+        if db.get_database_backend() != DatabaseBackend::Sqlite {
+            return Ok(());
+        }
+        for sql in CREATE_FTS {
+            db.execute_unprepared(sql).await?;
+        }
+        Ok(())
+    })
+}
+
+/// 将关键词按 jieba 分词，过滤掉纯标点，返回用于匹配与高亮的词列表
+pub fn split_keyword(keyword: &str) -> Vec<String> {
+    get_jieba()
+        .cut(keyword, false)
+        .into_iter()
+        .map(str::trim)
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 构造全文搜索的过滤条件，所有词都需命中
+/// SQLite 使用 FTS5 索引，其他数据库在 content_rich 上逐词 LIKE
+pub fn match_condition(backend: DatabaseBackend, words: &[String]) -> SimpleExpr {
+    match backend {
+        DatabaseBackend::Sqlite => {
+            // 每个词作为一个短语，避免用户输入被解析为 FTS5 语法
+            let query = words
+                .iter()
+                .map(|w| format!("\"{}\"", w.replace('"', "\"\"")))
+                .collect::<Vec<_>>()
+                .join(" ");
+            Expr::cust_with_values(
+                "message_records.id IN (SELECT rowid FROM message_records_fts \
+                 WHERE message_records_fts MATCH ?)",
+                [query],
+            )
+        }
+        _ => words
+            .iter()
+            .map(|w| {
+                let escaped = w
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_");
+                Expr::col(entity::Column::ContentRich)
+                    .like(LikeExpr::new(format!("%{}%", escaped)).escape('\\'))
+            })
+            .reduce(SimpleExpr::and)
+            .unwrap_or_else(|| Expr::value(true)),
+    }
+}
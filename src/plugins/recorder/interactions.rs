@@ -0,0 +1,203 @@
+use super::entity::Model as Record;
+use super::privacy;
+use crate::adapters::onebot::{LockedWriter, api};
+use crate::db::migrations::{create_index, create_table};
+use crate::dispatcher::detach;
+use crate::event::Context;
+use futures_util::future::BoxFuture;
+use sea_orm::sea_query::Index;
+use sea_orm::{ConnectionTrait, DatabaseTransaction, DbErr, EntityTrait, Set};
+use simd_json::base::ValueAsScalar;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// 消息之间的互动关系 (回复 / @)，与 message_records 一一对应的附表
+pub mod entity {
+    use sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+    #[sea_orm(table_name = "message_interactions")]
+    pub struct Model {
+        #[sea_orm(primary_key)]
+        pub id: i32,
+        pub record_id: i32, // 对应 message_records.id
+        pub group_id: i64,
+        pub user_id: i64,                  // 发起方
+        pub target_id: i64,                // 被回复 / 被 @ 的用户
+        pub kind: String,                  // "reply" 或 "at"
+        pub reply_message_id: Option<i64>, // 被回复消息的 message_id
+        pub time: i64,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+
+    impl ActiveModelBehavior for ActiveModel {}
+}
+
+use entity::Entity as Interactions;
+
+pub const KIND_REPLY: &str = "reply";
+pub const KIND_AT: &str = "at";
+
+/// 从消息段中解析出的互动目标
+#[derive(Default)]
+pub struct Targets {
+    pub reply_message_id: Option<i64>,
+    pub mentions: Vec<i64>,
+}
+
+pub(super) fn migrate_create_interactions(
+    db: &DatabaseTransaction,
+) -> BoxFuture<'_, Result<(), DbErr>> {
+    Box::pin(async move {
+        create_table(db, Interactions).await?;
+
+        let indexes = [
+            Index::create()
+                .name("idx_interactions_group_time")
+                .table(Interactions)
+                .col(entity::Column::GroupId)
+                .col(entity::Column::Time)
+                .if_not_exists()
+                .to_owned(),
+            Index::create()
+                .name("idx_interactions_user_time")
+                .table(Interactions)
+                .col(entity::Column::UserId)
+                .col(entity::Column::Time)
+                .if_not_exists()
+                .to_owned(),
+            Index::create()
+                .name("idx_interactions_target_time")
+                .table(Interactions)
+                .col(entity::Column::TargetId)
+                .col(entity::Column::Time)
+                .if_not_exists()
+                .to_owned(),
+        ];
+
+        for idx in indexes {
+            create_index(db, idx).await?;
+        }
+        Ok(())
+    })
+}
+
+// ================= 最近消息发送者缓存 =================
+
+/// 缓存的消息数量，超出后淘汰最早的记录
+const RECENT_CAPACITY: usize = 5000;
+
+#[derive(Default)]
+struct RecentSenders {
+    order: VecDeque<i64>,
+    senders: HashMap<i64, i64>,
+}
+
+static RECENT: OnceLock<Mutex<RecentSenders>> = OnceLock::new();
+
+fn recent() -> &'static Mutex<RecentSenders> {
+    RECENT.get_or_init(Mutex::default)
+}
+
+/// 记录 message_id 的发送者，回复最近的消息时无需再调用 get_msg
+pub fn remember_sender(message_id: i64, user_id: i64) {
+    let mut cache = recent().lock().unwrap();
+    if cache.senders.insert(message_id, user_id).is_none() {
+        cache.order.push_back(message_id);
+        if cache.order.len() > RECENT_CAPACITY
+            && let Some(old) = cache.order.pop_front()
+        {
+            cache.senders.remove(&old);
+        }
+    }
+}
+
+fn lookup_sender(message_id: i64) -> Option<i64> {
+    recent().lock().unwrap().senders.get(&message_id).copied()
+}
+
+// ================= 写入 =================
+
+fn build(record: &Record, target_id: i64, kind: &str, reply: Option<i64>) -> entity::ActiveModel {
+    entity::ActiveModel {
+        record_id: Set(record.id),
+        group_id: Set(record.group_id),
+        user_id: Set(record.user_id),
+        target_id: Set(target_id),
+        kind: Set(kind.to_string()),
+        reply_message_id: Set(reply),
+        time: Set(record.time),
+        ..Default::default()
+    }
+}
+
+/// 保存一条群消息的互动关系 (忽略私聊与针对自己的互动)
+/// 被回复的消息不在缓存中时，在后台通过 get_msg 查询其发送者
+pub async fn save<C: ConnectionTrait>(
+    ctx: &Context,
+    writer: LockedWriter,
+    db: &C,
+    record: &Record,
+    targets: Targets,
+) -> Result<(), DbErr> {
+    if record.group_id == 0 {
+        return Ok(());
+    }
+
+    let mut rows = Vec::new();
+    let mut mentioned = Vec::new();
+    for target in targets.mentions {
+        if target != 0
+            && target != record.user_id
+            && !mentioned.contains(&target)
+            && !privacy::is_opted_out(target)
+        {
+            mentioned.push(target);
+            rows.push(build(record, target, KIND_AT, None));
+        }
+    }
+
+    if let Some(reply_id) = targets.reply_message_id {
+        match lookup_sender(reply_id) {
+            Some(target) => {
+                if target != record.user_id && !privacy::is_opted_out(target) {
+                    rows.push(build(record, target, KIND_REPLY, Some(reply_id)));
+                }
+            }
+            None => {
+                let task_ctx = ctx.clone();
+                let record = record.clone();
+                detach("recorder_reply_target", async move {
+                    let target = match api::get_msg(&task_ctx, writer, reply_id as i32).await {
+                        Ok(res) => res
+                            .sender
+                            .other
+                            .get("user_id")
+                            .and_then(|v| v.as_i64().or_else(|| v.as_u64().map(|u| u as i64))),
+                        Err(e) => {
+                            debug!(target: "Plugin/Recorder", "查询被回复消息 {} 失败: {}", reply_id, e);
+                            None
+                        }
+                    };
+                    if let Some(target) =
+                        target.filter(|t| *t != record.user_id && !privacy::is_opted_out(*t))
+                    {
+                        remember_sender(reply_id, target);
+                        let row = build(&record, target, KIND_REPLY, Some(reply_id));
+                        if let Err(e) = Interactions::insert(row).exec(&task_ctx.db).await {
+                            error!(target: "Plugin/Recorder", "互动关系记录失败: {}", e);
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        return Ok(());
+    }
+    Interactions::insert_many(rows).exec(db).await?;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use super::{RecordEntity, entity, interactions, raw};
+use crate::db::store::{Store, StoreError};
+use chrono::Local;
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter, TransactionTrait,
+};
+use std::collections::HashSet;
+use std::sync::{OnceLock, RwLock};
+
+/// 选择不被记录的用户保存在 plugin_kv 的该命名空间下，键为 "opt_out:<user_id>"
+pub const NAMESPACE: &str = "privacy";
+const OPT_OUT_PREFIX: &str = "opt_out:";
+
+static OPTED_OUT: OnceLock<RwLock<HashSet<i64>>> = OnceLock::new();
+
+fn opted_out() -> &'static RwLock<HashSet<i64>> {
+    OPTED_OUT.get_or_init(RwLock::default)
+}
+
+/// 从持久化存储加载退出记录的用户列表
+pub async fn load(store: &Store) -> Result<usize, StoreError> {
+    let rows: Vec<(String, i64)> = store.list(OPT_OUT_PREFIX).await?;
+    let ids: HashSet<i64> = rows
+        .iter()
+        .filter_map(|(key, _)| key.strip_prefix(OPT_OUT_PREFIX)?.parse().ok())
+        .collect();
+    let count = ids.len();
+    *opted_out().write().unwrap() = ids;
+    Ok(count)
+}
+
+/// 用户是否已选择不被记录
+pub fn is_opted_out(user_id: i64) -> bool {
+    opted_out().read().unwrap().contains(&user_id)
+}
+
+/// 所有已选择不被记录的用户
+pub fn opted_out_users() -> Vec<i64> {
+    opted_out().read().unwrap().iter().copied().collect()
+}
+
+/// 设置用户是否退出记录，返回状态是否发生了变化
+pub async fn set_opt_out(store: &Store, user_id: i64, opt_out: bool) -> Result<bool, StoreError> {
+    let key = format!("{}{}", OPT_OUT_PREFIX, user_id);
+    let changed = if opt_out {
+        store.set(&key, &Local::now().timestamp()).await?;
+        opted_out().write().unwrap().insert(user_id)
+    } else {
+        store.delete(&key).await?;
+        opted_out().write().unwrap().remove(&user_id)
+    };
+    Ok(changed)
+}
+
+/// 删除用户在 message_records 及其附表中的全部数据，返回删除的消息数
+/// FTS 索引由触发器同步删除
+pub async fn purge_user(db: &DatabaseConnection, user_id: i64) -> Result<u64, DbErr> {
+    let txn = db.begin().await?;
+
+    let records = RecordEntity::delete_many()
+        .filter(entity::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?
+        .rows_affected;
+
+    interactions::entity::Entity::delete_many()
+        .filter(
+            Condition::any()
+                .add(interactions::entity::Column::UserId.eq(user_id))
+                .add(interactions::entity::Column::TargetId.eq(user_id)),
+        )
+        .exec(&txn)
+        .await?;
+
+    raw::entity::Entity::delete_many()
+        .filter(raw::entity::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?;
+
+    txn.commit().await?;
+    Ok(records)
+}
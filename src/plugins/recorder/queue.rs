@@ -0,0 +1,258 @@
+use super::{RecordActiveModel, get_jieba, interactions, raw};
+use crate::adapters::onebot::LockedWriter;
+use crate::event::Context;
+use crate::metrics;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set, TransactionTrait};
+use simd_json::OwnedValue;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{self, Instant};
+
+/// 一条待写入的消息记录
+pub struct Job {
+    pub record: RecordActiveModel,
+    /// 待分词的纯文本，由后台写入任务统一分词
+    pub text: String,
+    pub targets: interactions::Targets,
+    pub message_id: Option<i64>,
+    /// 原始消息段，仅在开启 store_raw 时存在
+    pub raw_message: Option<OwnedValue>,
+    /// 被回复消息不在缓存中时，需要通过该上下文调用 get_msg
+    pub ctx: Context,
+    pub writer: LockedWriter,
+}
+
+enum Command {
+    Write(Box<Job>),
+    Shutdown(oneshot::Sender<()>),
+}
+
+struct Queue {
+    tx: mpsc::Sender<Command>,
+    capacity: usize,
+}
+
+static QUEUE: OnceLock<Queue> = OnceLock::new();
+
+/// 启动后台写入任务 (重复调用无效)
+/// 队列容量与批量参数仅在启动时读取，修改后需重启生效
+pub fn start(db: DatabaseConnection, capacity: usize, batch_size: usize, interval: Duration) {
+    QUEUE.get_or_init(|| {
+        let capacity = capacity.max(1);
+        let (tx, rx) = mpsc::channel(capacity);
+        tokio::spawn(run(rx, db, batch_size.max(1), interval));
+        info!(
+            target: "Plugin/Recorder",
+            "后台写入已启动 (队列 {}，每批 {} 条 / {} ms)",
+            capacity,
+            batch_size,
+            interval.as_millis()
+        );
+        Queue { tx, capacity }
+    });
+}
+
+/// 提交一条记录；队列已满时等待写入任务腾出空间
+/// 写入任务未启动或已关闭时直接在当前任务中写入
+pub async fn push(job: Job) {
+    let Some(queue) = QUEUE.get() else {
+        let db = job.ctx.db.clone();
+        flush(&db, vec![job]).await;
+        return;
+    };
+
+    let cmd = match queue.tx.try_send(Command::Write(Box::new(job))) {
+        Ok(()) => None,
+        Err(mpsc::error::TrySendError::Full(cmd)) => {
+            metrics::inc_counter(metrics::RECORDER_QUEUE_FULL, &[]);
+            Some(cmd)
+        }
+        Err(mpsc::error::TrySendError::Closed(cmd)) => Some(cmd),
+    };
+
+    if let Some(cmd) = cmd
+        && let Err(mpsc::error::SendError(Command::Write(job))) = queue.tx.send(cmd).await
+    {
+        let db = job.ctx.db.clone();
+        flush(&db, vec![*job]).await;
+    }
+
+    report_depth(queue);
+}
+
+/// 关闭写入任务并等待队列中剩余的记录写完
+pub async fn shutdown() {
+    let Some(queue) = QUEUE.get() else {
+        return;
+    };
+    let (done_tx, done_rx) = oneshot::channel();
+    if queue.tx.send(Command::Shutdown(done_tx)).await.is_ok() {
+        let _ = done_rx.await;
+        info!(target: "Plugin/Recorder", "消息写入队列已清空");
+    }
+}
+
+fn report_depth(queue: &Queue) {
+    let depth = queue.capacity - queue.tx.capacity();
+    metrics::set_gauge(metrics::RECORDER_QUEUE_DEPTH, &[], depth as f64);
+}
+
+async fn run(
+    mut rx: mpsc::Receiver<Command>,
+    db: DatabaseConnection,
+    batch_size: usize,
+    interval: Duration,
+) {
+    let mut pending: Vec<Job> = Vec::with_capacity(batch_size);
+    // 本批第一条记录的最晚写入时间
+    let mut deadline = Instant::now();
+
+    loop {
+        tokio::select! {
+            cmd = rx.recv() => match cmd {
+                Some(Command::Write(job)) => {
+                    if pending.is_empty() {
+                        deadline = Instant::now() + interval;
+                    }
+                    pending.push(*job);
+                    if pending.len() >= batch_size {
+                        flush(&db, std::mem::take(&mut pending)).await;
+                    }
+                }
+                Some(Command::Shutdown(done)) => {
+                    rx.close();
+                    while let Ok(cmd) = rx.try_recv() {
+                        if let Command::Write(job) = cmd {
+                            pending.push(*job);
+                        }
+                    }
+                    flush(&db, pending).await;
+                    let _ = done.send(());
+                    return;
+                }
+                None => {
+                    flush(&db, pending).await;
+                    return;
+                }
+            },
+            _ = time::sleep_until(deadline), if !pending.is_empty() => {
+                flush(&db, std::mem::take(&mut pending)).await;
+            }
+        }
+    }
+}
+
+/// 在一个事务中写入一批记录，连同其原始消息与互动关系
+async fn flush(db: &DatabaseConnection, mut jobs: Vec<Job>) {
+    if jobs.is_empty() {
+        return;
+    }
+    let total = jobs.len() as u64;
+
+    let texts: Vec<String> = jobs
+        .iter_mut()
+        .map(|j| std::mem::take(&mut j.text))
+        .collect();
+    let tokens = tokio::task::spawn_blocking(move || {
+        let jieba = get_jieba();
+        texts
+            .iter()
+            .map(|t| {
+                if t.is_empty() {
+                    String::new()
+                } else {
+                    jieba.cut(t, false).join(" ")
+                }
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .unwrap_or_default();
+
+    let started = std::time::Instant::now();
+    let txn = match db.begin().await {
+        Ok(txn) => txn,
+        Err(e) => {
+            error!(target: "Plugin/Recorder", "开启写入事务失败，丢弃 {} 条记录: {}", total, e);
+            metrics::add_counter(metrics::RECORDER_ROWS_DROPPED, &[], total);
+            return;
+        }
+    };
+
+    // 每条记录使用独立的保存点：PostgreSQL 中任一语句出错会使整个事务进入中止状态，
+    // 回滚到保存点后才能继续写入同一批的其他记录
+    let mut written = 0;
+    for (i, job) in jobs.into_iter().enumerate() {
+        let mut record = job.record;
+        record.tokens = Set(tokens.get(i).cloned().unwrap_or_default());
+
+        let sp = match txn.begin().await {
+            Ok(sp) => sp,
+            Err(e) => {
+                error!(target: "Plugin/Recorder", "创建保存点失败: {}", e);
+                continue;
+            }
+        };
+
+        let model = match record.insert(&sp).await {
+            Ok(model) => model,
+            Err(e) => {
+                error!(target: "Plugin/Recorder", "消息记录失败: {}", e);
+                let _ = sp.rollback().await;
+                continue;
+            }
+        };
+
+        // 附表写入失败只回滚各自的嵌套保存点，保留消息记录本身
+        if let Some(message) = &job.raw_message
+            && let Ok(inner) = sp.begin().await
+        {
+            let reply_id = job.targets.reply_message_id;
+            match raw::save(&inner, &model, job.message_id, reply_id, message).await {
+                Ok(()) => {
+                    let _ = inner.commit().await;
+                }
+                Err(e) => {
+                    error!(target: "Plugin/Recorder", "原始消息记录失败: {}", e);
+                    let _ = inner.rollback().await;
+                }
+            }
+        }
+        if let Ok(inner) = sp.begin().await {
+            match interactions::save(&job.ctx, job.writer, &inner, &model, job.targets).await {
+                Ok(()) => {
+                    let _ = inner.commit().await;
+                }
+                Err(e) => {
+                    error!(target: "Plugin/Recorder", "互动关系记录失败: {}", e);
+                    let _ = inner.rollback().await;
+                }
+            }
+        }
+
+        match sp.commit().await {
+            Ok(()) => written += 1,
+            Err(e) => error!(target: "Plugin/Recorder", "释放保存点失败: {}", e),
+        }
+    }
+
+    if let Err(e) = txn.commit().await {
+        error!(target: "Plugin/Recorder", "提交写入事务失败，丢弃 {} 条记录: {}", total, e);
+        metrics::add_counter(metrics::RECORDER_ROWS_DROPPED, &[], total);
+        return;
+    }
+
+    metrics::observe(
+        metrics::DB_INSERT_LATENCY,
+        &[],
+        started.elapsed().as_secs_f64(),
+    );
+    metrics::add_counter(metrics::RECORDER_ROWS_WRITTEN, &[], written);
+    if written < total {
+        metrics::add_counter(metrics::RECORDER_ROWS_DROPPED, &[], total - written);
+    }
+    if let Some(queue) = QUEUE.get() {
+        report_depth(queue);
+    }
+}
@@ -0,0 +1,208 @@
+use super::entity::Model as Record;
+use crate::db::migrations::{create_index, create_table};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use futures_util::future::BoxFuture;
+use sea_orm::sea_query::Index;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use simd_json::OwnedValue;
+use simd_json::base::ValueAsArray;
+use simd_json::derived::{ValueObjectAccess, ValueObjectAccessAsScalar};
+use std::io::{Read, Write};
+
+/// 消息的原始内容 (gzip 压缩的消息段 JSON)，与 message_records 一一对应的附表
+/// 用于引用、防撤回、导出与重新发送等需要还原原消息的场景
+pub mod entity {
+    use sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+    #[sea_orm(table_name = "message_raw")]
+    pub struct Model {
+        #[sea_orm(primary_key)]
+        pub id: i32,
+        pub record_id: i32, // 对应 message_records.id
+        pub group_id: i64,
+        pub user_id: i64,
+        pub message_id: Option<i64>, // Bot 自身发送的消息在发送前没有 message_id
+        pub reply_message_id: Option<i64>, // 被回复消息的 message_id
+        pub image_hashes: String,    // 图片文件哈希 (逗号分隔)
+        pub segments: Vec<u8>,       // gzip 压缩的消息段 JSON
+        pub time: i64,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+
+    impl ActiveModelBehavior for ActiveModel {}
+}
+
+use entity::Entity as RawMessages;
+
+impl entity::Model {
+    /// 解压并还原消息段
+    pub fn message(&self) -> Option<OwnedValue> {
+        decompress(&self.segments)
+    }
+
+    /// 图片文件哈希列表
+    pub fn image_hashes(&self) -> Vec<&str> {
+        self.image_hashes
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+}
+
+pub(super) fn migrate_create_raw(db: &DatabaseTransaction) -> BoxFuture<'_, Result<(), DbErr>> {
+    Box::pin(async move {
+        create_table(db, RawMessages).await?;
+
+        let indexes = [
+            Index::create()
+                .name("idx_raw_record")
+                .table(RawMessages)
+                .col(entity::Column::RecordId)
+                .if_not_exists()
+                .to_owned(),
+            Index::create()
+                .name("idx_raw_group_message")
+                .table(RawMessages)
+                .col(entity::Column::GroupId)
+                .col(entity::Column::MessageId)
+                .if_not_exists()
+                .to_owned(),
+            Index::create()
+                .name("idx_raw_time")
+                .table(RawMessages)
+                .col(entity::Column::Time)
+                .if_not_exists()
+                .to_owned(),
+        ];
+
+        for idx in indexes {
+            create_index(db, idx).await?;
+        }
+        Ok(())
+    })
+}
+
+// ================= 编解码 =================
+
+fn compress(val: &OwnedValue) -> Option<Vec<u8>> {
+    let json = simd_json::to_vec(val).ok()?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&json).ok()?;
+    encoder.finish().ok()
+}
+
+fn decompress(bytes: &[u8]) -> Option<OwnedValue> {
+    let mut json = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut json).ok()?;
+    simd_json::to_owned_value(&mut json).ok()
+}
+
+/// 提取消息中所有图片的文件哈希
+/// 文件名本身是 32 位 MD5 时直接使用，否则对文件标识取 MD5
+pub fn image_hashes(msg: Option<&OwnedValue>) -> Vec<String> {
+    let Some(arr) = msg.and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    arr.iter()
+        .filter(|seg| seg.get_str("type") == Some("image"))
+        .filter_map(|seg| {
+            let data = seg.get("data")?;
+            let file = data
+                .get_str("file_unique")
+                .or_else(|| data.get_str("file"))
+                .or_else(|| data.get_str("url"))?;
+            let name = file.rsplit(['/', '\\']).next().unwrap_or(file);
+            let stem = name.split('.').next().unwrap_or(name);
+            if stem.len() == 32 && stem.chars().all(|c| c.is_ascii_hexdigit()) {
+                Some(stem.to_ascii_lowercase())
+            } else {
+                Some(format!("{:x}", md5::compute(file)))
+            }
+        })
+        .collect()
+}
+
+// ================= 读写 =================
+
+/// 保存一条消息的原始内容
+pub async fn save<C: ConnectionTrait>(
+    db: &C,
+    record: &Record,
+    message_id: Option<i64>,
+    reply_message_id: Option<i64>,
+    message: &OwnedValue,
+) -> Result<(), DbErr> {
+    let Some(segments) = compress(message) else {
+        warn!(target: "Plugin/Recorder", "原始消息序列化失败 (记录 {})", record.id);
+        return Ok(());
+    };
+    let row = entity::ActiveModel {
+        record_id: Set(record.id),
+        group_id: Set(record.group_id),
+        user_id: Set(record.user_id),
+        message_id: Set(message_id),
+        reply_message_id: Set(reply_message_id),
+        image_hashes: Set(image_hashes(Some(message)).join(",")),
+        segments: Set(segments),
+        time: Set(record.time),
+        ..Default::default()
+    };
+    RawMessages::insert(row).exec(db).await?;
+    Ok(())
+}
+
+/// 按 message_id 查找原始消息 (私聊的 group_id 为 0)
+pub async fn find_by_message_id(
+    db: &DatabaseConnection,
+    group_id: i64,
+    message_id: i64,
+) -> Result<Option<entity::Model>, DbErr> {
+    RawMessages::find()
+        .filter(entity::Column::GroupId.eq(group_id))
+        .filter(entity::Column::MessageId.eq(message_id))
+        .order_by_desc(entity::Column::Id)
+        .one(db)
+        .await
+}
+
+/// 按 message_records.id 查找原始消息
+pub async fn find_by_record_id(
+    db: &DatabaseConnection,
+    record_id: i32,
+) -> Result<Option<entity::Model>, DbErr> {
+    RawMessages::find()
+        .filter(entity::Column::RecordId.eq(record_id))
+        .one(db)
+        .await
+}
+
+/// 删除早于 cutoff 的原始消息，与 message_records 的保留策略一致
+pub async fn delete_before(db: &DatabaseConnection, cutoff: i64) -> Result<u64, DbErr> {
+    let res = RawMessages::delete_many()
+        .filter(entity::Column::Time.lt(cutoff))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
+
+/// 批量查找一组记录的原始消息
+pub async fn find_by_record_ids(
+    db: &DatabaseConnection,
+    record_ids: &[i32],
+) -> Result<Vec<entity::Model>, DbErr> {
+    if record_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    RawMessages::find()
+        .filter(entity::Column::RecordId.is_in(record_ids.iter().copied()))
+        .all(db)
+        .await
+}
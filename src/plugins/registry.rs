@@ -5,14 +5,21 @@
 register_plugins!(
     filter_meta_event,
     logger,
+    config_reload {
+        on_init: Some(config_reload::init)
+    },
+    backup {
+        on_init: Some(backup::init)
+    },
     recorder {
-        on_init: Some(recorder::init)
+        on_init: Some(recorder::init),
+        db_migrations: recorder::MIGRATIONS
     },
     media_transfer,
     sticker_saver,
     group_self_title,
     ping_pong {
-        on_init: Some(ping_pong::init)
+        db_migrations: ping_pong::MIGRATIONS
     },
     recall,
     echo,
@@ -21,17 +28,30 @@ register_plugins!(
     stats_visualizer {
         on_connected: Some(stats_visualizer::on_connected)
     },
+    annual_report,
+    chat_search,
+    chat_export,
+    privacy,
     card_reader,
     gif_lab,
     image_splitter,
     ciyi {
-        on_init: Some(ciyi::init)
+        db_migrations: ciyi::MIGRATIONS
     },
     web_shot,
     shindan {
-        on_init: Some(shindan::init)
+        on_init: Some(shindan::init),
+        db_migrations: shindan::MIGRATIONS,
+        on_config_changed: Some(shindan::on_config_changed)
     },
     oai {
-        on_init: Some(oai::init)
+        on_init: Some(oai::init),
+        on_config_changed: Some(oai::on_config_changed)
+    },
+    webhook,
+    http_api {
+        on_init: Some(http_api::init),
+        on_connected: Some(http_api::on_connected),
+        on_disconnected: Some(http_api::on_disconnected)
     },
 );
@@ -1,7 +1,7 @@
 use crate::adapters::onebot::{LockedWriter, send_msg};
 use crate::config::build_config;
 use crate::event::{Context, EventType};
-use crate::plugins::{PluginError, get_config};
+use crate::plugins::{PluginError, get_config, validate_as};
 use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use simd_json::OwnedValue;
@@ -32,6 +32,11 @@ pub fn default_config() -> TomlValue {
     build_config(RepeaterConfig::default())
 }
 
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &TomlValue) -> Result<(), (String, String)> {
+    validate_as::<RepeaterConfig>(value)
+}
+
 // ================= 状态定义 =================
 
 #[derive(Debug, Default, Clone)]
@@ -82,7 +87,7 @@ pub fn handle(
                 return Ok(Some(ctx));
             };
 
-            let content = if let EventType::Onebot(ev) = &ctx.event {
+            let content = if let EventType::Onebot(ev) = &*ctx.event {
                 ev.get("message")
                     .cloned()
                     .unwrap_or_else(|| OwnedValue::from(Vec::<OwnedValue>::new()))
@@ -131,7 +136,7 @@ pub fn handle(
             }
         }
         // === 场景 B: 消息发送前 (Before Send) ===
-        else if let EventType::BeforeSend(packet) = &ctx.event
+        else if let EventType::BeforeSend(packet) = &*ctx.event
             && let Some(gid) = packet.group_id()
         {
             let channel_id = gid.to_string();
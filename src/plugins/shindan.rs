@@ -1,9 +1,11 @@
 use crate::adapters::onebot::LockedWriter;
 use crate::command::match_command;
 use crate::config::build_config;
+use crate::db::migrations::{Migration, create_index, create_table};
 use crate::event::Context;
-use crate::plugins::{PluginError, get_config};
+use crate::plugins::{PluginError, get_config, validate_as};
 use futures_util::future::BoxFuture;
+use sea_orm::{DatabaseTransaction, DbErr};
 use shindan_maker::ShindanDomain;
 use std::sync::OnceLock;
 use toml::Value;
@@ -17,6 +19,7 @@ pub mod storage;
 pub mod utils;
 
 use config::PluginConfig;
+use entity::{item_stats, user_stats};
 use storage::Storage;
 use utils::extract_args;
 
@@ -31,10 +34,54 @@ pub fn default_config() -> Value {
     build_config(PluginConfig::default())
 }
 
-pub fn init(ctx: Context) -> BoxFuture<'static, std::result::Result<(), PluginError>> {
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<PluginConfig>(value)
+}
+
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    name: "create_shindan_stats",
+    up: migrate_create_stats,
+}];
+
+fn migrate_create_stats(db: &DatabaseTransaction) -> BoxFuture<'_, std::result::Result<(), DbErr>> {
+    Box::pin(async move {
+        create_table(db, user_stats::Entity).await?;
+        create_table(db, item_stats::Entity).await?;
+
+        let idx_user = sea_orm::sea_query::Index::create()
+            .name("idx_shindan_user_count")
+            .table(user_stats::Entity)
+            .col(user_stats::Column::Count)
+            .if_not_exists()
+            .to_owned();
+        create_index(db, idx_user).await?;
+
+        let idx_item = sea_orm::sea_query::Index::create()
+            .name("idx_shindan_item_count")
+            .table(item_stats::Entity)
+            .col(item_stats::Column::Count)
+            .if_not_exists()
+            .to_owned();
+        create_index(db, idx_item).await
+    })
+}
+
+pub fn init(_ctx: Context) -> BoxFuture<'static, std::result::Result<(), PluginError>> {
     Box::pin(async move {
         let storage = get_storage();
-        storage.init(&ctx.db).await;
+        storage.init().await;
+        Ok(())
+    })
+}
+
+/// 配置重载时同步刷新神断列表 (手动编辑 shindans.toml 后发送“重载配置”即可生效)
+pub fn on_config_changed(
+    _ctx: Context,
+) -> BoxFuture<'static, std::result::Result<(), PluginError>> {
+    Box::pin(async move {
+        get_storage().load_list().await;
         Ok(())
     })
 }
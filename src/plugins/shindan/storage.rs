@@ -1,9 +1,8 @@
 use crate::plugins::get_data_dir;
 use sea_orm::ActiveValue::Set;
 use sea_orm::QuerySelect;
-use sea_orm::Schema;
 use sea_orm::sea_query::{Expr, OnConflict};
-use sea_orm::{ConnectionTrait, DatabaseConnection, EntityTrait, QueryOrder};
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder};
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 use tokio::fs;
@@ -27,14 +26,14 @@ impl Storage {
         }
     }
 
-    /// 初始化：确定路径、加载或创建 TOML、建表
-    pub async fn init(&self, db: &DatabaseConnection) {
+    /// 初始化：确定路径、加载或创建 TOML
+    pub async fn init(&self) {
         // 1. 获取插件数据目录 data/shindan
         let data_dir = match get_data_dir("shindan").await {
             Ok(p) => p,
             Err(e) => {
                 error!(target: "Shindan", "获取数据目录失败: {}", e);
-                PathBuf::from("data/shindan")
+                crate::config::data_dir().join("shindan")
             }
         };
 
@@ -61,6 +60,19 @@ impl Storage {
         }
 
         // 3. 加载 shindans.toml
+        self.load_list().await;
+    }
+
+    /// 从 shindans.toml 重新加载神断列表 (文件被手动修改后可调用刷新)
+    pub async fn load_list(&self) {
+        let toml_path = {
+            let guard = self.config_path.read().unwrap();
+            guard.clone()
+        };
+        if toml_path.as_os_str().is_empty() {
+            return;
+        }
+
         match fs::read_to_string(&toml_path).await {
             Ok(content) => match toml::from_str::<ShindanList>(&content) {
                 Ok(list) => {
@@ -72,57 +84,6 @@ impl Storage {
             },
             Err(e) => error!(target: "Shindan", "读取 shindans.toml 失败: {}", e),
         }
-
-        // 4. 初始化数据库表
-        let builder = db.get_database_backend();
-        let schema = Schema::new(builder);
-
-        if let Err(e) = db
-            .execute(
-                builder.build(
-                    schema
-                        .create_table_from_entity(user_stats::Entity)
-                        .if_not_exists(),
-                ),
-            )
-            .await
-        {
-            error!(target: "Shindan", "初始化 user_stats 表失败: {}", e);
-        }
-
-        if let Err(e) = db
-            .execute(
-                builder.build(
-                    schema
-                        .create_table_from_entity(item_stats::Entity)
-                        .if_not_exists(),
-                ),
-            )
-            .await
-        {
-            error!(target: "Shindan", "初始化 item_stats 表失败: {}", e);
-        }
-
-        // 创建索引
-        let idx_user = sea_orm::sea_query::Index::create()
-            .name("idx_shindan_user_count")
-            .table(user_stats::Entity)
-            .col(user_stats::Column::Count)
-            .if_not_exists()
-            .to_owned();
-        if let Err(e) = db.execute(builder.build(&idx_user)).await {
-            error!(target: "Shindan", "创建索引 idx_shindan_user_count 失败: {}", e);
-        }
-
-        let idx_item = sea_orm::sea_query::Index::create()
-            .name("idx_shindan_item_count")
-            .table(item_stats::Entity)
-            .col(item_stats::Column::Count)
-            .if_not_exists()
-            .to_owned();
-        if let Err(e) = db.execute(builder.build(&idx_item)).await {
-            error!(target: "Shindan", "创建索引 idx_shindan_item_count 失败: {}", e);
-        }
     }
 
     async fn save_toml_internal(&self, list: &ShindanList) {
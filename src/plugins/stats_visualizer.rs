@@ -2,10 +2,10 @@ use crate::adapters::onebot::{LockedWriter, send_msg};
 use crate::command::get_prefixes;
 use crate::config::build_config;
 use crate::db::queries;
-use crate::db::utils::get_time_range;
+use crate::db::utils::{get_time_range, parse_time_range, time_expr_pattern};
 use crate::event::Context;
 use crate::message::Message;
-use crate::plugins::{PluginError, get_config, word_cloud};
+use crate::plugins::{PluginError, get_config, validate_as, word_cloud};
 use chrono::Local;
 use futures_util::future::BoxFuture;
 use regex::Regex;
@@ -33,6 +33,10 @@ pub struct StatsConfig {
     pub daily_push_time: String,
     #[serde(default)]
     pub daily_push_scope: String,
+
+    /// 热词对比的基线天数
+    #[serde(default = "default_hot_words_baseline_days")]
+    pub hot_words_baseline_days: i64,
 }
 
 fn default_font_family() -> String {
@@ -51,6 +55,10 @@ fn default_daily_push_time() -> String {
     "23:30:00".to_string()
 }
 
+fn default_hot_words_baseline_days() -> i64 {
+    30
+}
+
 pub fn default_config() -> Value {
     build_config(StatsConfig {
         enabled: true,
@@ -60,27 +68,70 @@ pub fn default_config() -> Value {
         daily_push_enabled: false,
         daily_push_time: "23:30:00".to_string(),
         daily_push_scope: "本群".to_string(),
+        hot_words_baseline_days: 30,
     })
 }
 
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<StatsConfig>(value)
+}
+
 // ================= 正则匹配 =================
 
 static REGEX_GLOBAL: OnceLock<Regex> = OnceLock::new();
 static REGEX_NORMAL: OnceLock<Regex> = OnceLock::new();
+static REGEX_ACTIVITY: OnceLock<Regex> = OnceLock::new();
+static REGEX_TERMS: OnceLock<Regex> = OnceLock::new();
+static REGEX_SOCIAL: OnceLock<Regex> = OnceLock::new();
 
 fn get_regex_global() -> &'static Regex {
     REGEX_GLOBAL.get_or_init(|| {
-        Regex::new(
-            r"^所有群(今日|昨日|本周|上周|近7天|近30天|本月|上月|今年|去年|总)发言(排行榜|走势)$",
-        )
+        Regex::new(&format!(
+            r"^所有群({})发言(排行榜|走势)$",
+            time_expr_pattern()
+        ))
         .unwrap()
     })
 }
 
 fn get_regex_normal() -> &'static Regex {
     REGEX_NORMAL.get_or_init(|| {
-        Regex::new(r"^(?:(本群|跨群|我的))?(今日|昨日|本周|上周|近7天|近30天|本月|上月|今年|去年|总)(发言|表情包|消息类型)(排行榜|走势)$")
-            .unwrap()
+        Regex::new(&format!(
+            r"^(?:(本群|跨群|我的))?({})(发言|表情包|消息类型)(排行榜|走势)$",
+            time_expr_pattern()
+        ))
+        .unwrap()
+    })
+}
+
+fn get_regex_activity() -> &'static Regex {
+    REGEX_ACTIVITY.get_or_init(|| {
+        Regex::new(&format!(
+            r"^(?:(本群|跨群|我的))?({})?(活跃时段|周活跃分布|发言日历)$",
+            time_expr_pattern()
+        ))
+        .unwrap()
+    })
+}
+
+fn get_regex_terms() -> &'static Regex {
+    REGEX_TERMS.get_or_init(|| {
+        Regex::new(&format!(
+            r"^(?:(本群|跨群|我的))?({})?(?:(热词)|话题趋势\s*(\S+))$",
+            time_expr_pattern()
+        ))
+        .unwrap()
+    })
+}
+
+fn get_regex_social() -> &'static Regex {
+    REGEX_SOCIAL.get_or_init(|| {
+        Regex::new(&format!(
+            r"^(?:(?:本群)?({t})?(关系图)|(?:我的)?({t})?(好友))$",
+            t = time_expr_pattern()
+        ))
+        .unwrap()
     })
 }
 
@@ -129,6 +180,38 @@ pub fn handle(
                 let c = caps.get(4).map_or("", |m| m.as_str());
                 let final_scope = if s.is_empty() { "本群" } else { s };
                 (final_scope, t, d, c, false)
+            } else if let Some(caps) = get_regex_activity().captures(content) {
+                let s = caps.get(1).map_or("本群", |m| m.as_str());
+                let c = caps.get(3).map_or("", |m| m.as_str());
+                // 未指定时间时：日历默认今年，其余默认近30天
+                let default_time = if c == "发言日历" {
+                    "今年"
+                } else {
+                    "近30天"
+                };
+                let t = caps.get(2).map_or(default_time, |m| m.as_str());
+                (s, t, "", c, false)
+            } else if let Some(caps) = get_regex_terms().captures(content) {
+                let s = caps.get(1).map_or("本群", |m| m.as_str());
+                // 热词默认看今日，话题趋势默认看近30天 (关键词放在 data_type 中)
+                if caps.get(3).is_some() {
+                    let t = caps.get(2).map_or("今日", |m| m.as_str());
+                    (s, t, "", "热词", false)
+                } else {
+                    let t = caps.get(2).map_or("近30天", |m| m.as_str());
+                    let term = caps.get(4).map_or("", |m| m.as_str());
+                    (s, t, term, "话题趋势", false)
+                }
+            } else if let Some(caps) = get_regex_social().captures(content) {
+                // 关系图固定为本群，好友固定为我的 (跨群)，默认近30天
+                if let Some(c) = caps.get(2) {
+                    let t = caps.get(1).map_or("近30天", |m| m.as_str());
+                    ("本群", t, "", c.as_str(), false)
+                } else {
+                    let t = caps.get(3).map_or("近30天", |m| m.as_str());
+                    let c = caps.get(4).map_or("", |m| m.as_str());
+                    ("我的", t, "", c, false)
+                }
             } else {
                 return Ok(Some(ctx));
             };
@@ -154,7 +237,17 @@ pub fn handle(
             scope, time_str, data_type, chart_type, is_all_groups
         );
 
-        let (start_time, end_time) = get_time_range(time_str);
+        let Some((start_time, end_time)) = parse_time_range(time_str) else {
+            let _ = send_msg(
+                &ctx,
+                writer,
+                group_id,
+                Some(user_id),
+                format!("无法识别的时间: {}", time_str),
+            )
+            .await;
+            return Ok(None);
+        };
 
         let (query_group, query_user) = match scope {
             "本群" => (group_id, None),
@@ -163,10 +256,15 @@ pub fn handle(
             _ => (None, None),
         };
 
-        let title = if is_all_groups {
-            format!("所有群 {} {} {}", time_str, data_type, chart_type)
+        let title_scope = if is_all_groups { "所有群" } else { scope };
+        let title = if chart_type == "话题趋势" {
+            format!("{} {} 话题趋势「{}」", title_scope, time_str, data_type)
         } else {
-            format!("{} {} {} {}", scope, time_str, data_type, chart_type)
+            [title_scope, time_str, data_type, chart_type]
+                .into_iter()
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
         };
 
         let result_img = chart::generate(
@@ -8,9 +8,19 @@ use crate::plugins::get_config;
 use crate::plugins::stats_visualizer::{StatsConfig, default_config};
 
 use self::avatar::prepare_avatars;
-use self::data_loader::{BarData, SeriesData, fetch_bar_data, fetch_line_data};
-use self::renderer::{draw_bar_chart, draw_line_chart};
+use self::data_loader::{
+    BarData, SeriesData, fetch_bar_data, fetch_calendar_data, fetch_friend_data,
+    fetch_heatmap_data, fetch_hot_words, fetch_hourly_data, fetch_line_data, fetch_network_data,
+    fetch_term_trend,
+};
+use self::renderer::{
+    draw_bar_chart, draw_calendar, draw_heatmap, draw_line_chart, draw_network, draw_radial_chart,
+};
 
+/// 热词榜展示的词数
+const HOT_WORDS_LIMIT: usize = 15;
+
+/// 生成图表，话题趋势的 data_type 为要统计的关键词
 #[allow(clippy::too_many_arguments)]
 pub async fn generate(
     ctx: &Context,
@@ -28,7 +38,68 @@ pub async fn generate(
     let config: StatsConfig = get_config(ctx, "stats_visualizer")
         .unwrap_or_else(|| serde::Deserialize::deserialize(default_config()).unwrap());
 
-    // 1. 走势图
+    // 1. 活跃度图表
+    match chart_type {
+        "活跃时段" => {
+            let hours =
+                fetch_hourly_data(db, query_group, query_user, start_time, end_time).await?;
+            return draw_radial_chart(&config, title, &hours);
+        }
+        "周活跃分布" => {
+            let grid =
+                fetch_heatmap_data(db, query_group, query_user, start_time, end_time).await?;
+            return draw_heatmap(&config, title, &grid);
+        }
+        "发言日历" => {
+            let calendar =
+                fetch_calendar_data(db, query_group, query_user, start_time, end_time).await?;
+            return draw_calendar(&config, title, &calendar);
+        }
+        _ => {}
+    }
+
+    // 2. 词频图表
+    match chart_type {
+        "热词" => {
+            let mut bar_data = fetch_hot_words(
+                db,
+                query_group,
+                query_user,
+                start_time,
+                end_time,
+                config.hot_words_baseline_days,
+                HOT_WORDS_LIMIT,
+            )
+            .await?;
+            prepare_avatars(&mut bar_data).await;
+            return draw_bar_chart(&config, title, bar_data);
+        }
+        "话题趋势" => {
+            let chart_data =
+                fetch_term_trend(db, query_group, query_user, data_type, start_time, end_time)
+                    .await?;
+            return draw_line_chart(&config, title, chart_data);
+        }
+        _ => {}
+    }
+
+    // 3. 互动关系
+    match chart_type {
+        "关系图" => {
+            let network = fetch_network_data(db, query_group, start_time, end_time).await?;
+            return draw_network(&config, title, &network);
+        }
+        "好友" => {
+            let uid = query_user.unwrap_or(sender_id);
+            let mut bar_data =
+                fetch_friend_data(db, query_group, uid, start_time, end_time).await?;
+            prepare_avatars(&mut bar_data).await;
+            return draw_bar_chart(&config, title, bar_data);
+        }
+        _ => {}
+    }
+
+    // 4. 走势图
     if chart_type == "走势" {
         let chart_data: Vec<SeriesData> = fetch_line_data(
             db,
@@ -44,7 +115,7 @@ pub async fn generate(
         return draw_line_chart(&config, title, chart_data);
     }
 
-    // 2. 柱状图 / 排行榜
+    // 5. 柱状图 / 排行榜
     let mut bar_data: Vec<BarData> = fetch_bar_data(
         db,
         is_all_groups,
@@ -57,9 +128,9 @@ pub async fn generate(
     )
     .await?;
 
-    // 3. 准备头像
+    // 6. 准备头像
     prepare_avatars(&mut bar_data).await;
 
-    // 4. 绘图
+    // 7. 绘图
     draw_bar_chart(&config, title, bar_data)
 }
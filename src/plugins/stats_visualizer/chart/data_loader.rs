@@ -1,10 +1,12 @@
+use crate::db::dialect::flag_sum;
 use crate::db::queries;
 use crate::plugins::recorder::entity::{self, Entity as MessageLogs};
+use crate::plugins::word_cloud::stopwords::is_meaningful;
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
 use plotters::style::RGBColor;
-use sea_orm::sea_query::{Func, SimpleExpr};
 use sea_orm::{
-    ColumnTrait, Condition, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
-    QueryOrder, QuerySelect, QueryTrait,
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, QueryTrait,
 };
 use std::collections::HashMap;
 
@@ -189,7 +191,6 @@ pub async fn fetch_line_data(
             }
         }
 
-        use chrono::{Local, TimeZone};
         for (i, count) in counts.iter().enumerate() {
             let label_time = Local
                 .timestamp_opt(start_time + (i as i64 * 3600), 0)
@@ -363,14 +364,7 @@ pub async fn fetch_bar_data(
         };
 
         let count = if data_type == "表情包" {
-            use sea_orm::prelude::Expr;
-            use sea_orm::sea_query::Alias;
-
-            let count_expr = Func::cast_as(
-                Expr::col(entity::Column::IsAnimEmoji),
-                Alias::new("integer"),
-            );
-            let sum_expr = SimpleExpr::from(Func::sum(count_expr));
+            let sum_expr = flag_sum(db.get_database_backend(), "is_anim_emoji");
 
             let res: Option<i64> = count_query
                 .select_only()
@@ -414,3 +408,404 @@ pub async fn fetch_bar_data(
 
     Ok(bar_data)
 }
+
+// 发言日历数据 (按本地日期)
+pub struct CalendarData {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub counts: HashMap<NaiveDate, i64>,
+}
+
+/// 获取 24 小时活跃分布 (下标为小时)
+pub async fn fetch_hourly_data(
+    db: &DatabaseConnection,
+    query_group: Option<i64>,
+    query_user: Option<i64>,
+    start_time: i64,
+    end_time: i64,
+) -> Result<[i64; 24], String> {
+    let rows = queries::get_hourly_activity(db, query_group, query_user, start_time, end_time)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut hours = [0i64; 24];
+    for r in rows {
+        if let Some(slot) = hours.get_mut(r.hour as usize) {
+            *slot += r.count;
+        }
+    }
+    Ok(hours)
+}
+
+/// 获取 星期×小时 热力数据 (行: 周一..周日，列: 0..23 时)
+pub async fn fetch_heatmap_data(
+    db: &DatabaseConnection,
+    query_group: Option<i64>,
+    query_user: Option<i64>,
+    start_time: i64,
+    end_time: i64,
+) -> Result<[[i64; 24]; 7], String> {
+    let rows = queries::get_heatmap_data(db, query_group, query_user, start_time, end_time)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut grid = [[0i64; 24]; 7];
+    for r in rows {
+        // 记录中的 weekday 以周日为 0，这里转换为周一在前
+        let row = ((r.weekday + 6) % 7) as usize;
+        if let Some(slot) = grid.get_mut(row).and_then(|h| h.get_mut(r.hour as usize)) {
+            *slot += r.count;
+        }
+    }
+    Ok(grid)
+}
+
+/// 时间戳对应的本地日期
+fn local_date(ts: i64) -> NaiveDate {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|t| t.date_naive())
+        .unwrap_or_default()
+}
+
+/// 获取发言日历数据
+/// 从年初开始的范围会补全到年底，超过一年的范围只保留最近 53 周
+pub async fn fetch_calendar_data(
+    db: &DatabaseConnection,
+    query_group: Option<i64>,
+    query_user: Option<i64>,
+    start_time: i64,
+    end_time: i64,
+) -> Result<CalendarData, String> {
+    let mut start = local_date(start_time);
+    let mut end = local_date((end_time - 1).max(start_time));
+
+    if (end - start).num_days() > 53 * 7 {
+        start = end - Duration::weeks(53) + Duration::days(1);
+    } else if start.ordinal() == 1 && start.year() == end.year() {
+        end = NaiveDate::from_ymd_opt(start.year(), 12, 31).unwrap_or(end);
+    }
+
+    let trend = queries::get_daily_trend(db, query_group, query_user, start_time, end_time)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let counts = trend
+        .into_iter()
+        .filter_map(|t| {
+            NaiveDate::parse_from_str(&t.date, "%Y-%m-%d")
+                .ok()
+                .map(|d| (d, t.count))
+        })
+        .filter(|(d, _)| *d >= start && *d <= end)
+        .collect();
+
+    Ok(CalendarData { start, end, counts })
+}
+
+/// 热词至少要出现的次数 (过滤偶发词)
+const HOT_WORD_MIN_COUNT: usize = 3;
+
+/// 热词爆发度的平滑项 (次/天)，避免基线中从未出现的词得分无穷大
+const HOT_WORD_SMOOTHING: f64 = 1.0;
+
+/// 按页遍历时间段内带日期的分词语料，每读取一页调用一次 f
+async fn for_each_corpus_page(
+    db: &DatabaseConnection,
+    query_group: Option<i64>,
+    query_user: Option<i64>,
+    keyword: Option<&str>,
+    start_time: i64,
+    end_time: i64,
+    mut f: impl FnMut(&[queries::DailyText]),
+) -> Result<(), String> {
+    let mut after_id = 0;
+    loop {
+        let rows = queries::get_daily_corpus_page(
+            db,
+            query_group,
+            query_user,
+            keyword,
+            start_time,
+            end_time,
+            after_id,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+        let Some(last) = rows.last() else {
+            return Ok(());
+        };
+        after_id = last.id;
+        f(&rows);
+    }
+}
+
+/// 获取话题趋势：关键词每日出现次数，无记录的日期补 0
+pub async fn fetch_term_trend(
+    db: &DatabaseConnection,
+    query_group: Option<i64>,
+    query_user: Option<i64>,
+    term: &str,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Vec<SeriesData>, String> {
+    // 只统计完整匹配的分词，避免 "猫" 命中 "熊猫"
+    let mut counts: HashMap<NaiveDate, i64> = HashMap::new();
+    for_each_corpus_page(
+        db,
+        query_group,
+        query_user,
+        Some(term),
+        start_time,
+        end_time,
+        |rows| {
+            for row in rows {
+                let n = row
+                    .content_text
+                    .split_whitespace()
+                    .filter(|w| *w == term)
+                    .count() as i64;
+                if n > 0
+                    && let Ok(date) = NaiveDate::parse_from_str(&row.date, "%Y-%m-%d")
+                {
+                    *counts.entry(date).or_insert(0) += n;
+                }
+            }
+        },
+    )
+    .await?;
+
+    // 从首次出现的日期开始画，避免 "总" 之类的范围从 1970 年开始
+    let Some(first) = counts.keys().min().copied() else {
+        return Err(format!("该时间段内没有人提到「{}」", term));
+    };
+    let start = local_date(start_time).max(first);
+    let end = local_date((end_time - 1).max(start_time));
+
+    let points = start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .map(|d| ChartDataPoint {
+            label: d.format("%Y-%m-%d").to_string(),
+            value: counts.get(&d).copied().unwrap_or(0),
+        })
+        .collect();
+
+    Ok(vec![SeriesData {
+        name: term.to_string(),
+        color: RGBColor(59, 130, 246),
+        points,
+    }])
+}
+
+/// 获取热词：与此前 baseline_days 天相比突然变多的词
+/// 按爆发度排序：本期日均次数 / (基线日均次数 + 平滑项)，平时天天出现的词即使次数多也排不上来
+pub async fn fetch_hot_words(
+    db: &DatabaseConnection,
+    query_group: Option<i64>,
+    query_user: Option<i64>,
+    start_time: i64,
+    end_time: i64,
+    baseline_days: i64,
+    limit: usize,
+) -> Result<Vec<BarData>, String> {
+    let mut tf: HashMap<String, usize> = HashMap::new();
+    for_each_corpus_page(
+        db,
+        query_group,
+        query_user,
+        None,
+        start_time,
+        end_time,
+        |rows| {
+            for row in rows {
+                for w in row.content_text.split_whitespace() {
+                    if is_meaningful(w) {
+                        *tf.entry(w.to_string()).or_insert(0) += 1;
+                    }
+                }
+            }
+        },
+    )
+    .await?;
+    tf.retain(|_, c| *c >= HOT_WORD_MIN_COUNT);
+    if tf.is_empty() {
+        return Err("该时间段内没有足够的聊天记录".to_string());
+    }
+
+    let baseline_days = baseline_days.max(1);
+    let baseline_start = start_time - baseline_days * 86400;
+
+    // 候选词在基线期间的总出现次数
+    let mut baseline_counts: HashMap<&str, usize> = HashMap::new();
+    for_each_corpus_page(
+        db,
+        query_group,
+        query_user,
+        None,
+        baseline_start,
+        start_time,
+        |rows| {
+            for row in rows {
+                for w in row.content_text.split_whitespace() {
+                    if let Some((key, _)) = tf.get_key_value(w) {
+                        *baseline_counts.entry(key.as_str()).or_insert(0) += 1;
+                    }
+                }
+            }
+        },
+    )
+    .await?;
+
+    let target_days = ((end_time - start_time) as f64 / 86400.0).ceil().max(1.0);
+    let mut scored: Vec<(&str, usize, f64)> = tf
+        .iter()
+        .map(|(w, &count)| {
+            let w = w.as_str();
+            let rate = count as f64 / target_days;
+            let baseline_rate =
+                baseline_counts.get(w).copied().unwrap_or(0) as f64 / baseline_days as f64;
+            (w, count, rate / (baseline_rate + HOT_WORD_SMOOTHING))
+        })
+        .collect();
+    scored.sort_by(|a, b| {
+        b.2.total_cmp(&a.2)
+            .then_with(|| b.1.cmp(&a.1))
+            .then_with(|| a.0.cmp(b.0))
+    });
+
+    Ok(scored
+        .into_iter()
+        .take(limit)
+        .map(|(w, count, _)| BarData {
+            label: w.to_string(),
+            value: count as i64,
+            user_id: None,
+            avatar_url: None,
+            avatar_img: None,
+            theme_color: RGBColor(249, 115, 22),
+        })
+        .collect())
+}
+
+/// 关系图最多展示的成员数
+const NETWORK_MAX_NODES: u64 = 30;
+
+// 关系图节点 (成员)
+pub struct NetworkNode {
+    pub name: String,
+    pub count: i64, // 发言量，决定节点大小
+}
+
+// 关系图数据，edges 为 (节点下标, 节点下标, 双向互动次数之和)
+pub struct NetworkData {
+    pub nodes: Vec<NetworkNode>,
+    pub edges: Vec<(usize, usize, i64)>,
+}
+
+/// 获取群关系图数据：发言最多的成员为节点，成员之间的回复与 @ 次数为边
+pub async fn fetch_network_data(
+    db: &DatabaseConnection,
+    query_group: Option<i64>,
+    start_time: i64,
+    end_time: i64,
+) -> Result<NetworkData, String> {
+    let ranking =
+        queries::get_user_ranking(db, query_group, start_time, end_time, NETWORK_MAX_NODES)
+            .await
+            .map_err(|e| e.to_string())?;
+    let edges = queries::get_interaction_edges(db, query_group, None, start_time, end_time)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let index: HashMap<i64, usize> = ranking
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (r.user_id, i))
+        .collect();
+
+    // 合并两个方向的互动
+    let mut weights: HashMap<(usize, usize), i64> = HashMap::new();
+    for e in edges {
+        if let (Some(&a), Some(&b)) = (index.get(&e.user_id), index.get(&e.target_id))
+            && a != b
+        {
+            *weights.entry((a.min(b), a.max(b))).or_insert(0) += e.count;
+        }
+    }
+
+    if weights.is_empty() {
+        return Err("该时间段内没有回复或 @ 记录".to_string());
+    }
+
+    let mut edges: Vec<(usize, usize, i64)> =
+        weights.into_iter().map(|((a, b), w)| (a, b, w)).collect();
+    edges.sort_by(|x, y| y.2.cmp(&x.2));
+
+    let nodes = ranking
+        .into_iter()
+        .map(|r| NetworkNode {
+            name: r.nickname,
+            count: r.count,
+        })
+        .collect();
+
+    Ok(NetworkData { nodes, edges })
+}
+
+/// 获取与某用户互动最多的人 (回复 / @ 双向合计)
+pub async fn fetch_friend_data(
+    db: &DatabaseConnection,
+    query_group: Option<i64>,
+    user_id: i64,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Vec<BarData>, String> {
+    let limit = 20;
+
+    let edges =
+        queries::get_interaction_edges(db, query_group, Some(user_id), start_time, end_time)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    let mut counts: HashMap<i64, i64> = HashMap::new();
+    for e in edges {
+        let other = if e.user_id == user_id {
+            e.target_id
+        } else {
+            e.user_id
+        };
+        if other != user_id {
+            *counts.entry(other).or_insert(0) += e.count;
+        }
+    }
+
+    let mut friends: Vec<(i64, i64)> = counts.into_iter().collect();
+    friends.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    friends.truncate(limit);
+
+    if friends.is_empty() {
+        return Err("该时间段内没有回复或 @ 记录".to_string());
+    }
+
+    let ids: Vec<i64> = friends.iter().map(|(id, _)| *id).collect();
+    let names: HashMap<i64, String> = queries::get_user_nicknames(db, query_group, &ids)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|n| (n.user_id, n.nickname))
+        .collect();
+
+    Ok(friends
+        .into_iter()
+        .map(|(id, count)| BarData {
+            label: names.get(&id).cloned().unwrap_or_else(|| id.to_string()),
+            value: count,
+            user_id: Some(id),
+            avatar_url: Some(format!("https://q1.qlogo.cn/g?b=qq&nk={}&s=640", id)),
+            avatar_img: None,
+            theme_color: RGBColor(59, 130, 246),
+        })
+        .collect())
+}
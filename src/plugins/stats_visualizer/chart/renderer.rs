@@ -1,10 +1,10 @@
-use super::data_loader::{BarData, SeriesData};
+use super::data_loader::{BarData, CalendarData, NetworkData, SeriesData};
 use super::utils::{
     ColorScheme, get_contrast_color, get_font, get_font_family, get_font_with_color,
     mix_with_white, overlay_image, save_rgba_to_base64, truncate_text_to_fit,
 };
 use crate::plugins::stats_visualizer::StatsConfig;
-use chrono::Local;
+use chrono::{Datelike, Local};
 use image::{Rgba, RgbaImage};
 use plotters::prelude::*;
 use plotters::style::text_anchor::{HPos, Pos, VPos};
@@ -401,3 +401,631 @@ pub fn draw_line_chart(
 
     save_rgba_to_base64(rgba_image)
 }
+
+// ================= 活跃度图表 =================
+
+/// GitHub 风格的绿色分级 (0 为无数据)
+const CALENDAR_LEVELS: [RGBColor; 5] = [
+    RGBColor(235, 237, 240),
+    RGBColor(155, 233, 168),
+    RGBColor(64, 196, 99),
+    RGBColor(48, 161, 78),
+    RGBColor(33, 110, 57),
+];
+
+const WEEKDAY_NAMES: [&str; 7] = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"];
+
+/// 绘制日期与标题，返回标题下方的 Y 坐标
+fn draw_header<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    config: &StatsConfig,
+    title: &str,
+    width: u32,
+    s: u32,
+) -> Result<u32, String> {
+    let padding_top = 25 * s;
+    let header_font_size = 20 * s;
+    let title_font_size = 28 * s;
+    let gap = 10 * s;
+
+    let now_str = Local::now().format("%Y-%m-%d %H:%M").to_string();
+    let header_style = get_font(config, header_font_size)
+        .pos(Pos::new(HPos::Center, VPos::Top))
+        .color(&RGBColor(100, 116, 139));
+    root.draw_text(
+        &now_str,
+        &header_style,
+        (width as i32 / 2, padding_top as i32),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let title_y = padding_top + header_font_size + gap;
+    let title_style = get_font(config, title_font_size).pos(Pos::new(HPos::Center, VPos::Top));
+    root.draw_text(title, &title_style, (width as i32 / 2, title_y as i32))
+        .map_err(|e| e.to_string())?;
+
+    Ok(title_y + title_font_size + 20 * s)
+}
+
+fn rgb_buffer_to_base64(buffer: &[u8], width: u32, height: u32) -> Result<String, String> {
+    let mut rgba_image = RgbaImage::new(width, height);
+    for (i, pixel) in rgba_image.pixels_mut().enumerate() {
+        let idx = i * 3;
+        *pixel = Rgba([buffer[idx], buffer[idx + 1], buffer[idx + 2], 255]);
+    }
+    save_rgba_to_base64(rgba_image)
+}
+
+/// 按数值占最大值的比例取主题色深浅
+fn intensity_color(value: i64, max_val: i64) -> RGBColor {
+    if value <= 0 {
+        return CALENDAR_LEVELS[0];
+    }
+    let ratio = value as f32 / max_val.max(1) as f32;
+    mix_with_white(ColorScheme::default().primary, 0.2 + 0.8 * ratio)
+}
+
+/// 绘制 24 小时环形活跃图 (0 点位于正上方，顺时针)
+pub fn draw_radial_chart(
+    config: &StatsConfig,
+    title: &str,
+    hours: &[i64; 24],
+) -> Result<String, String> {
+    let total: i64 = hours.iter().sum();
+    if total == 0 {
+        return Err("暂无数据".to_string());
+    }
+    let max_val = hours.iter().copied().max().unwrap_or(1).max(1);
+    let (peak_hour, peak_val) = hours
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, v)| **v)
+        .map(|(h, v)| (h, *v))
+        .unwrap_or((0, 0));
+
+    let s = 2u32;
+    let width = 800 * s;
+    let height = 920 * s;
+    let colors = ColorScheme::default();
+
+    let mut buffer = vec![0u8; (width * height * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+        root.fill(&colors.background).map_err(|e| e.to_string())?;
+
+        let content_top = draw_header(&root, config, title, width, s)?;
+
+        let cx = width as f64 / 2.0;
+        let cy = content_top as f64 + 400.0 * s as f64;
+        let r_inner = 120.0 * s as f64;
+        let r_outer = 330.0 * s as f64;
+
+        // 角度换算：hour 可为小数
+        let point = |hour: f64, r: f64| -> (i32, i32) {
+            let a = hour / 24.0 * std::f64::consts::TAU - std::f64::consts::FRAC_PI_2;
+            ((cx + r * a.cos()) as i32, (cy + r * a.sin()) as i32)
+        };
+
+        // 1. 参考圆环
+        for frac in [0.25, 0.5, 0.75, 1.0] {
+            let r = r_inner + (r_outer - r_inner) * frac;
+            root.draw(&Circle::new(
+                (cx as i32, cy as i32),
+                r as i32,
+                colors.grid_line.stroke_width(s),
+            ))
+            .map_err(|e| e.to_string())?;
+        }
+
+        // 2. 每小时一个扇形条
+        for (h, &v) in hours.iter().enumerate() {
+            if v == 0 {
+                continue;
+            }
+            let r = r_inner + (r_outer - r_inner) * v as f64 / max_val as f64;
+            let (a0, a1) = (h as f64 + 0.08, h as f64 + 0.92);
+            let steps = 8;
+
+            let mut pts: Vec<(i32, i32)> = (0..=steps)
+                .map(|i| point(a0 + (a1 - a0) * i as f64 / steps as f64, r))
+                .collect();
+            pts.extend(
+                (0..=steps)
+                    .rev()
+                    .map(|i| point(a0 + (a1 - a0) * i as f64 / steps as f64, r_inner)),
+            );
+
+            root.draw(&Polygon::new(pts, intensity_color(v, max_val).filled()))
+                .map_err(|e| e.to_string())?;
+        }
+
+        // 3. 小时刻度
+        for h in 0..24 {
+            let color = if h == peak_hour {
+                colors.primary
+            } else {
+                colors.text_secondary
+            };
+            let style = get_font_with_color(config, 16 * s, &color)
+                .pos(Pos::new(HPos::Center, VPos::Center));
+            root.draw_text(
+                &h.to_string(),
+                &style,
+                point(h as f64 + 0.5, r_outer + 24.0 * s as f64),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        // 4. 中心摘要
+        let center = (cx as i32, cy as i32);
+        let label_style = get_font_with_color(config, 18 * s, &colors.text_secondary)
+            .pos(Pos::new(HPos::Center, VPos::Center));
+        let peak_style = get_font_with_color(config, 30 * s, &colors.primary)
+            .pos(Pos::new(HPos::Center, VPos::Center));
+
+        root.draw_text(
+            "高峰时段",
+            &label_style,
+            (center.0, center.1 - 45 * s as i32),
+        )
+        .map_err(|e| e.to_string())?;
+        root.draw_text(
+            &format!("{:02}:00-{:02}:00", peak_hour, (peak_hour + 1) % 24),
+            &peak_style,
+            center,
+        )
+        .map_err(|e| e.to_string())?;
+        root.draw_text(
+            &format!("{} / {} 条", peak_val, total),
+            &label_style,
+            (center.0, center.1 + 45 * s as i32),
+        )
+        .map_err(|e| e.to_string())?;
+
+        root.present().map_err(|e| e.to_string())?;
+    }
+
+    rgb_buffer_to_base64(&buffer, width, height)
+}
+
+/// 绘制 星期×小时 热力图
+pub fn draw_heatmap(
+    config: &StatsConfig,
+    title: &str,
+    grid: &[[i64; 24]; 7],
+) -> Result<String, String> {
+    let total: i64 = grid.iter().flatten().sum();
+    if total == 0 {
+        return Err("暂无数据".to_string());
+    }
+    let max_val = grid.iter().flatten().copied().max().unwrap_or(1).max(1);
+
+    let s = 2u32;
+    let padding = 24 * s;
+    let cell = 34 * s;
+    let gap = 4 * s;
+    let label_width = 70 * s;
+    let hour_label_height = 30 * s;
+    let footer_height = 70 * s;
+    let colors = ColorScheme::default();
+
+    let width = padding * 2 + label_width + 24 * (cell + gap);
+    // 标题区高度与 draw_header 保持一致
+    let header_height = 25 * s + 20 * s + 10 * s + 28 * s + 20 * s;
+    let height = header_height + hour_label_height + 7 * (cell + gap) + footer_height + padding;
+
+    let mut buffer = vec![0u8; (width * height * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+        root.fill(&colors.background).map_err(|e| e.to_string())?;
+
+        let content_top = draw_header(&root, config, title, width, s)?;
+        let grid_left = (padding + label_width) as i32;
+        let grid_top = (content_top + hour_label_height) as i32;
+        let step = (cell + gap) as i32;
+
+        // 1. 小时标签 (每 3 小时)
+        let hour_style = get_font_with_color(config, 14 * s, &colors.text_secondary)
+            .pos(Pos::new(HPos::Center, VPos::Bottom));
+        for h in (0..24).step_by(3) {
+            root.draw_text(
+                &format!("{}时", h),
+                &hour_style,
+                (
+                    grid_left + h * step + cell as i32 / 2,
+                    grid_top - 6 * s as i32,
+                ),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        // 2. 星期标签与格子
+        let day_style = get_font_with_color(config, 16 * s, &colors.text_primary)
+            .pos(Pos::new(HPos::Right, VPos::Center));
+        for (row, hours) in grid.iter().enumerate() {
+            let y = grid_top + row as i32 * step;
+            root.draw_text(
+                WEEKDAY_NAMES[row],
+                &day_style,
+                (grid_left - 12 * s as i32, y + cell as i32 / 2),
+            )
+            .map_err(|e| e.to_string())?;
+
+            for (h, &v) in hours.iter().enumerate() {
+                let x = grid_left + h as i32 * step;
+                root.draw(&Rectangle::new(
+                    [(x, y), (x + cell as i32, y + cell as i32)],
+                    intensity_color(v, max_val).filled(),
+                ))
+                .map_err(|e| e.to_string())?;
+            }
+        }
+
+        // 3. 底部摘要与图例
+        let (peak_row, peak_hour, peak_val) = grid
+            .iter()
+            .enumerate()
+            .flat_map(|(r, hours)| hours.iter().enumerate().map(move |(h, v)| (r, h, *v)))
+            .max_by_key(|(_, _, v)| *v)
+            .unwrap_or((0, 0, 0));
+
+        let footer_y = grid_top + 7 * step + 20 * s as i32;
+        let summary_style = get_font_with_color(config, 16 * s, &colors.text_secondary)
+            .pos(Pos::new(HPos::Left, VPos::Top));
+        root.draw_text(
+            &format!(
+                "共 {} 条 · 最活跃: {} {:02}:00 ({} 条)",
+                total, WEEKDAY_NAMES[peak_row], peak_hour, peak_val
+            ),
+            &summary_style,
+            (grid_left, footer_y),
+        )
+        .map_err(|e| e.to_string())?;
+
+        draw_legend(
+            &root,
+            config,
+            (width - padding) as i32,
+            footer_y,
+            &[0.0, 0.25, 0.5, 0.75, 1.0]
+                .map(|r| intensity_color((r * max_val as f64) as i64, max_val)),
+            s,
+        )?;
+
+        root.present().map_err(|e| e.to_string())?;
+    }
+
+    rgb_buffer_to_base64(&buffer, width, height)
+}
+
+/// 在右对齐位置绘制 "少 □□□□□ 多" 图例
+fn draw_legend<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    config: &StatsConfig,
+    right: i32,
+    top: i32,
+    levels: &[RGBColor],
+    s: u32,
+) -> Result<(), String> {
+    let colors = ColorScheme::default();
+    let swatch = 16 * s as i32;
+    let gap = 4 * s as i32;
+    let text_style = get_font_with_color(config, 14 * s, &colors.text_secondary)
+        .pos(Pos::new(HPos::Right, VPos::Center));
+
+    root.draw_text("多", &text_style, (right, top + swatch / 2))
+        .map_err(|e| e.to_string())?;
+
+    let mut x = right - 22 * s as i32 - swatch;
+    for color in levels.iter().rev() {
+        root.draw(&Rectangle::new(
+            [(x, top), (x + swatch, top + swatch)],
+            color.filled(),
+        ))
+        .map_err(|e| e.to_string())?;
+        x -= swatch + gap;
+    }
+
+    root.draw_text(
+        "少",
+        &text_style,
+        (x + swatch - 2 * s as i32, top + swatch / 2),
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 绘制 GitHub 风格的发言日历 (列为周，行为周一..周日)
+pub fn draw_calendar(
+    config: &StatsConfig,
+    title: &str,
+    data: &CalendarData,
+) -> Result<String, String> {
+    let total: i64 = data.counts.values().sum();
+    if total == 0 {
+        return Err("暂无数据".to_string());
+    }
+    let max_val = data.counts.values().copied().max().unwrap_or(1).max(1);
+
+    let grid_start =
+        data.start - chrono::Duration::days(data.start.weekday().num_days_from_monday() as i64);
+    let weeks = ((data.end - grid_start).num_days() / 7 + 1) as u32;
+
+    let s = 2u32;
+    let padding = 24 * s;
+    let cell = 22 * s;
+    let gap = 4 * s;
+    let label_width = 50 * s;
+    let month_label_height = 28 * s;
+    let footer_height = 70 * s;
+    let colors = ColorScheme::default();
+
+    let width = (padding * 2 + label_width + weeks * (cell + gap)).max(700 * s);
+    let header_height = 25 * s + 20 * s + 10 * s + 28 * s + 20 * s;
+    let height = header_height + month_label_height + 7 * (cell + gap) + footer_height + padding;
+
+    let level_of = |v: i64| -> RGBColor {
+        if v <= 0 {
+            CALENDAR_LEVELS[0]
+        } else {
+            let level = ((v as f64 / max_val as f64) * 4.0).ceil() as usize;
+            CALENDAR_LEVELS[level.clamp(1, 4)]
+        }
+    };
+
+    let mut buffer = vec![0u8; (width * height * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+        root.fill(&colors.background).map_err(|e| e.to_string())?;
+
+        let content_top = draw_header(&root, config, title, width, s)?;
+        // 内容较窄时居中
+        let grid_width = label_width + weeks * (cell + gap);
+        let grid_left = ((width - grid_width) / 2 + label_width) as i32;
+        let grid_top = (content_top + month_label_height) as i32;
+        let step = (cell + gap) as i32;
+
+        // 1. 星期标签 (隔行显示)
+        let day_style = get_font_with_color(config, 13 * s, &colors.text_secondary)
+            .pos(Pos::new(HPos::Right, VPos::Center));
+        for row in [0usize, 2, 4] {
+            root.draw_text(
+                WEEKDAY_NAMES[row],
+                &day_style,
+                (
+                    grid_left - 8 * s as i32,
+                    grid_top + row as i32 * step + cell as i32 / 2,
+                ),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        // 2. 日期格子与月份标签
+        let month_style = get_font_with_color(config, 13 * s, &colors.text_secondary)
+            .pos(Pos::new(HPos::Left, VPos::Bottom));
+        let mut last_month = 0;
+        let mut day = data.start;
+        while day <= data.end {
+            let offset = (day - grid_start).num_days();
+            let col = (offset / 7) as i32;
+            let row = (offset % 7) as i32;
+            let x = grid_left + col * step;
+            let y = grid_top + row * step;
+
+            if day.month() != last_month {
+                last_month = day.month();
+                root.draw_text(
+                    &format!("{}月", last_month),
+                    &month_style,
+                    (x, grid_top - 6 * s as i32),
+                )
+                .map_err(|e| e.to_string())?;
+            }
+
+            let v = data.counts.get(&day).copied().unwrap_or(0);
+            root.draw(&Rectangle::new(
+                [(x, y), (x + cell as i32, y + cell as i32)],
+                level_of(v).filled(),
+            ))
+            .map_err(|e| e.to_string())?;
+
+            day += chrono::Duration::days(1);
+        }
+
+        // 3. 底部摘要与图例
+        let active_days = data.counts.values().filter(|v| **v > 0).count();
+        let (best_day, best_val) = data
+            .counts
+            .iter()
+            .max_by_key(|(d, v)| (**v, std::cmp::Reverse(**d)))
+            .map(|(d, v)| (*d, *v))
+            .unwrap_or((data.start, 0));
+
+        let footer_y = grid_top + 7 * step + 20 * s as i32;
+        let summary_style = get_font_with_color(config, 16 * s, &colors.text_secondary)
+            .pos(Pos::new(HPos::Left, VPos::Top));
+        root.draw_text(
+            &format!(
+                "共 {} 条 · 活跃 {} 天 · 最多 {} ({} 条)",
+                total,
+                active_days,
+                best_day.format("%m-%d"),
+                best_val
+            ),
+            &summary_style,
+            (grid_left - label_width as i32 + 8 * s as i32, footer_y),
+        )
+        .map_err(|e| e.to_string())?;
+
+        draw_legend(
+            &root,
+            config,
+            (width - padding) as i32,
+            footer_y,
+            &CALENDAR_LEVELS,
+            s,
+        )?;
+
+        root.present().map_err(|e| e.to_string())?;
+    }
+
+    rgb_buffer_to_base64(&buffer, width, height)
+}
+
+/// 力导向布局迭代次数
+const LAYOUT_ITERATIONS: usize = 300;
+
+/// Fruchterman-Reingold 力导向布局，返回 [0, 1] 区间内的节点坐标
+/// 以圆周为初始位置，结果是确定的 (同样的数据画出同样的图)
+fn layout_network(n: usize, edges: &[(usize, usize, i64)]) -> Vec<(f64, f64)> {
+    let mut pos: Vec<(f64, f64)> = (0..n)
+        .map(|i| {
+            let a = i as f64 / n as f64 * std::f64::consts::TAU;
+            (0.5 + 0.4 * a.cos(), 0.5 + 0.4 * a.sin())
+        })
+        .collect();
+    if n < 2 {
+        return pos;
+    }
+
+    let k = (1.0 / n as f64).sqrt();
+    let max_w = edges.iter().map(|e| e.2).max().unwrap_or(1).max(1) as f64;
+    let mut temp = 0.1;
+
+    for _ in 0..LAYOUT_ITERATIONS {
+        let mut disp = vec![(0.0f64, 0.0f64); n];
+
+        // 节点间斥力
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let (dx, dy) = (pos[i].0 - pos[j].0, pos[i].1 - pos[j].1);
+                let d = (dx * dx + dy * dy).sqrt().max(0.01);
+                let f = k * k / d;
+                disp[i].0 += dx / d * f;
+                disp[i].1 += dy / d * f;
+                disp[j].0 -= dx / d * f;
+                disp[j].1 -= dy / d * f;
+            }
+        }
+
+        // 边的引力，互动越多拉得越近
+        for &(a, b, w) in edges {
+            let (dx, dy) = (pos[a].0 - pos[b].0, pos[a].1 - pos[b].1);
+            let d = (dx * dx + dy * dy).sqrt().max(0.01);
+            let f = d * d / k * (0.5 + w as f64 / max_w);
+            disp[a].0 -= dx / d * f;
+            disp[a].1 -= dy / d * f;
+            disp[b].0 += dx / d * f;
+            disp[b].1 += dy / d * f;
+        }
+
+        for (p, d) in pos.iter_mut().zip(&disp) {
+            // 向中心的弱引力，避免孤立节点飘到边缘
+            let (dx, dy) = (d.0 + (0.5 - p.0) * k, d.1 + (0.5 - p.1) * k);
+            let len = (dx * dx + dy * dy).sqrt();
+            if len > 0.0 {
+                let step = len.min(temp);
+                p.0 = (p.0 + dx / len * step).clamp(0.0, 1.0);
+                p.1 = (p.1 + dy / len * step).clamp(0.0, 1.0);
+            }
+        }
+        temp *= 0.98;
+    }
+
+    // 缩放到填满 [0, 1]
+    let (min_x, max_x) = pos.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+        (lo.min(p.0), hi.max(p.0))
+    });
+    let (min_y, max_y) = pos.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+        (lo.min(p.1), hi.max(p.1))
+    });
+    let (span_x, span_y) = ((max_x - min_x).max(1e-6), (max_y - min_y).max(1e-6));
+    pos.iter()
+        .map(|p| ((p.0 - min_x) / span_x, (p.1 - min_y) / span_y))
+        .collect()
+}
+
+/// 绘制群成员关系图 (节点大小为发言量，连线粗细为互动次数)
+pub fn draw_network(
+    config: &StatsConfig,
+    title: &str,
+    data: &NetworkData,
+) -> Result<String, String> {
+    if data.nodes.is_empty() || data.edges.is_empty() {
+        return Err("暂无数据".to_string());
+    }
+
+    let s = 2u32;
+    let width = 900 * s;
+    let height = 1000 * s;
+    let colors = ColorScheme::default();
+
+    let positions = layout_network(data.nodes.len(), &data.edges);
+    let max_count = data.nodes.iter().map(|n| n.count).max().unwrap_or(1).max(1);
+    let max_weight = data.edges.iter().map(|e| e.2).max().unwrap_or(1).max(1);
+
+    let mut buffer = vec![0u8; (width * height * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+        root.fill(&colors.background).map_err(|e| e.to_string())?;
+
+        let content_top = draw_header(&root, config, title, width, s)?;
+
+        // 四周留出节点半径与名字的空间
+        let margin = 80.0 * s as f64;
+        let (left, top) = (margin, content_top as f64 + 40.0 * s as f64);
+        let (area_w, area_h) = (width as f64 - 2.0 * margin, height as f64 - top - margin);
+        let to_px = |p: (f64, f64)| -> (i32, i32) {
+            ((left + p.0 * area_w) as i32, (top + p.1 * area_h) as i32)
+        };
+
+        // 1. 连线 (从弱到强绘制，强连线在上层)
+        for &(a, b, w) in data.edges.iter().rev() {
+            let ratio = w as f64 / max_weight as f64;
+            let stroke = (1.0 + 7.0 * ratio) * s as f64;
+            let color = mix_with_white(colors.primary, (0.15 + 0.6 * ratio) as f32);
+            root.draw(&PathElement::new(
+                vec![to_px(positions[a]), to_px(positions[b])],
+                color.stroke_width(stroke as u32),
+            ))
+            .map_err(|e| e.to_string())?;
+        }
+
+        // 2. 节点与名字
+        let font_family = get_font_family(config);
+        let font_obj = (font_family, 16 * s).into_font();
+        let name_style = get_font_with_color(config, 16 * s, &colors.text_primary)
+            .pos(Pos::new(HPos::Center, VPos::Top));
+
+        for (node, &p) in data.nodes.iter().zip(&positions) {
+            let center = to_px(p);
+            let ratio = (node.count as f64 / max_count as f64).sqrt();
+            let radius = ((8.0 + 28.0 * ratio) * s as f64) as i32;
+
+            root.draw(&Circle::new(
+                center,
+                radius,
+                intensity_color(node.count, max_count).filled(),
+            ))
+            .map_err(|e| e.to_string())?;
+            root.draw(&Circle::new(
+                center,
+                radius,
+                colors.background.stroke_width(2 * s),
+            ))
+            .map_err(|e| e.to_string())?;
+
+            let name = truncate_text_to_fit(&font_obj, &node.name, 140 * s);
+            root.draw_text(
+                &name,
+                &name_style,
+                (center.0, center.1 + radius + 4 * s as i32),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        root.present().map_err(|e| e.to_string())?;
+    }
+
+    rgb_buffer_to_base64(&buffer, width, height)
+}
@@ -1,8 +1,9 @@
 use crate::adapters::onebot::{LockedWriter, send_msg};
 use crate::config::build_config;
+use crate::dispatcher::detach;
 use crate::event::Context;
 use crate::message::Message;
-use crate::plugins::{PluginError, get_config};
+use crate::plugins::{PluginError, get_config, validate_as};
 use anyhow::{Result, anyhow};
 use cdp_html_shot::{Browser, CaptureOptions, ImageFormat, Viewport};
 use futures_util::future::BoxFuture;
@@ -50,6 +51,11 @@ pub fn default_config() -> Value {
     })
 }
 
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<Config>(value)
+}
+
 // ================= Core Logic =================
 
 async fn capture_url(url: &str, config: &Config, browser_path: Option<String>) -> Result<String> {
@@ -184,7 +190,7 @@ pub fn handle(
         }
 
         // 提取 URL
-        let url_candidate = if let crate::event::EventType::Onebot(event) = &ctx.event {
+        let url_candidate = if let crate::event::EventType::Onebot(event) = &*ctx.event {
             if let Some(arr) = event.get_array("message") {
                 arr.iter()
                     .filter(|seg| seg.get_str("type") == Some("text"))
@@ -208,21 +214,29 @@ pub fn handle(
                 }
             }
 
-            // 执行截图
+            // 执行截图 (耗时较长，分离到后台执行，不阻塞同频道的后续消息)
             info!(target: "WebShot", "Capturing: {}", url);
 
-            match capture_url(&url, &config, browser_path).await {
-                Ok(base64_img) => {
-                    let msg = Message::new()
-                        .reply(msg_event.message_id())
-                        .image(format!("base64://{}", base64_img));
-
-                    send_msg(&ctx, writer, group_id, Some(user_id), msg).await?;
+            let message_id = msg_event.message_id();
+            let task_ctx = ctx.clone();
+            detach("web_shot", async move {
+                match capture_url(&url, &config, browser_path).await {
+                    Ok(base64_img) => {
+                        let msg = Message::new()
+                            .reply(message_id)
+                            .image(format!("base64://{}", base64_img));
+
+                        if let Err(e) =
+                            send_msg(&task_ctx, writer, group_id, Some(user_id), msg).await
+                        {
+                            error!(target: "WebShot", "Error sending {}: {}", url, e);
+                        }
+                    }
+                    Err(e) => {
+                        error!(target: "WebShot", "Error capturing {}: {}", url, e);
+                    }
                 }
-                Err(e) => {
-                    error!(target: "WebShot", "Error capturing {}: {}", url, e);
-                }
-            }
+            });
         }
 
         Ok(Some(ctx))
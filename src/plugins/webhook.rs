@@ -0,0 +1,293 @@
+use crate::adapters::onebot::{LockedWriter, send_msg};
+use crate::config::build_config;
+use crate::event::{Context, EventType};
+use crate::message::Message;
+use crate::plugins::{PluginError, get_config, get_data_dir, validate_as};
+use chrono::Local;
+use futures_util::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use simd_json::derived::ValueObjectAccessAsScalar;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use toml::Value;
+
+// ================= 配置定义 =================
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Endpoint {
+    /// 推送地址
+    pub url: String,
+    /// HMAC-SHA256 签名密钥，为空则不签名
+    /// 签名内容为 "<X-Ayjx-Timestamp>.<请求体>"，接收方应校验签名并拒绝时间戳过旧的请求以防重放
+    #[serde(default)]
+    pub secret: String,
+    /// 匹配的 post_type (空表示全部，如 ["message", "notice"])
+    #[serde(default)]
+    pub post_types: Vec<String>,
+    /// 匹配的群号 (空表示全部)
+    #[serde(default)]
+    pub groups: Vec<i64>,
+    /// 匹配的用户 (空表示全部)
+    #[serde(default)]
+    pub users: Vec<i64>,
+    /// 对 raw_message 的正则匹配 (空表示不限制)
+    #[serde(default)]
+    pub pattern: String,
+    #[serde(default = "default_timeout")]
+    pub timeout_seconds: u64,
+    #[serde(default = "default_retries")]
+    pub max_retries: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct WebhookConfig {
+    enabled: bool,
+    #[serde(default)]
+    endpoints: Vec<Endpoint>,
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+pub fn default_config() -> Value {
+    build_config(WebhookConfig {
+        enabled: false,
+        endpoints: vec![Endpoint {
+            url: "http://127.0.0.1:8080/ayjx".to_string(),
+            post_types: vec!["message".to_string()],
+            timeout_seconds: default_timeout(),
+            max_retries: default_retries(),
+            ..Default::default()
+        }],
+    })
+}
+
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<WebhookConfig>(value)
+}
+
+// ================= 工具函数 =================
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+static REGEX_CACHE: OnceLock<Mutex<HashMap<String, Option<Regex>>>> = OnceLock::new();
+
+fn client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// 编译并缓存正则，非法正则只警告一次并视为不匹配
+fn pattern_matches(pattern: &str, text: &str) -> bool {
+    let cache = REGEX_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut guard = cache.lock().unwrap();
+    let re = guard.entry(pattern.to_string()).or_insert_with(|| {
+        Regex::new(pattern)
+            .map_err(|e| warn!(target: "Plugin/Webhook", "无效的正则 '{}': {}", pattern, e))
+            .ok()
+    });
+    re.as_ref().is_some_and(|r| r.is_match(text))
+}
+
+fn endpoint_matches(ep: &Endpoint, ev: &crate::event::Event) -> bool {
+    let post_type = ev.get_str("post_type").unwrap_or("");
+    if !ep.post_types.is_empty() && !ep.post_types.iter().any(|p| p == post_type) {
+        return false;
+    }
+
+    let group_id = ev
+        .get_i64("group_id")
+        .or_else(|| ev.get_u64("group_id").map(|v| v as i64));
+    if !ep.groups.is_empty() && !group_id.is_some_and(|g| ep.groups.contains(&g)) {
+        return false;
+    }
+
+    let user_id = ev
+        .get_i64("user_id")
+        .or_else(|| ev.get_u64("user_id").map(|v| v as i64));
+    if !ep.users.is_empty() && !user_id.is_some_and(|u| ep.users.contains(&u)) {
+        return false;
+    }
+
+    if !ep.pattern.is_empty() {
+        let raw = ev.get_str("raw_message").unwrap_or("");
+        return pattern_matches(&ep.pattern, raw);
+    }
+
+    true
+}
+
+/// 计算 HMAC-SHA256 签名 (十六进制小写)，时间戳一并签名，防止截获的请求被换上新时间戳重放
+fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 可接受任意长度密钥");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 投递事件，失败时按指数退避重试；返回响应体
+async fn deliver(ep: &Endpoint, body: &str) -> Result<String, String> {
+    let mut last_err = String::new();
+
+    for attempt in 0..=ep.max_retries {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_secs(1 << (attempt - 1).min(5))).await;
+        }
+
+        let timestamp = Local::now().timestamp().to_string();
+        let mut req = client()
+            .post(&ep.url)
+            .timeout(Duration::from_secs(ep.timeout_seconds))
+            .header("Content-Type", "application/json")
+            .header("X-Ayjx-Timestamp", &timestamp)
+            .body(body.to_string());
+
+        if !ep.secret.is_empty() {
+            req = req.header(
+                "X-Ayjx-Signature",
+                format!("sha256={}", sign(&ep.secret, &timestamp, body.as_bytes())),
+            );
+        }
+
+        match req.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                return resp.text().await.map_err(|e| e.to_string());
+            }
+            Ok(resp) => last_err = format!("HTTP {}", resp.status()),
+            Err(e) => last_err = e.to_string(),
+        }
+
+        debug!(
+            target: "Plugin/Webhook",
+            "推送 {} 失败 (第 {} 次): {}",
+            ep.url,
+            attempt + 1,
+            last_err
+        );
+    }
+
+    Err(last_err)
+}
+
+/// 记录无法送达的事件到 data/webhook/dead_letter.jsonl
+async fn write_dead_letter(url: &str, body: &str, reason: &str) {
+    let dir = match get_data_dir("webhook").await {
+        Ok(d) => d,
+        Err(e) => {
+            error!(target: "Plugin/Webhook", "获取数据目录失败: {}", e);
+            return;
+        }
+    };
+
+    let line = serde_json::json!({
+        "time": Local::now().to_rfc3339(),
+        "url": url,
+        "reason": reason,
+        "event": body,
+    })
+    .to_string();
+
+    let res = async {
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("dead_letter.jsonl"))
+            .await?;
+        file.write_all(format!("{}\n", line).as_bytes()).await
+    }
+    .await;
+
+    if let Err(e) = res {
+        error!(target: "Plugin/Webhook", "写入死信日志失败: {}", e);
+    }
+}
+
+/// 将响应体解析为消息：支持消息段数组或纯文本字符串
+fn parse_reply(body: &str) -> Option<Message> {
+    let trimmed = body.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    match serde_json::from_str::<serde_json::Value>(trimmed).ok()? {
+        serde_json::Value::String(s) if !s.is_empty() => Some(Message::from(s)),
+        v @ serde_json::Value::Array(_) => serde_json::from_value::<Message>(v)
+            .ok()
+            .filter(|m| !m.0.is_empty()),
+        _ => None,
+    }
+}
+
+// ================= 插件入口 =================
+
+pub fn handle(
+    ctx: Context,
+    writer: LockedWriter,
+) -> BoxFuture<'static, Result<Option<Context>, PluginError>> {
+    Box::pin(async move {
+        let ev = match &*ctx.event {
+            EventType::Onebot(ev) => ev,
+            _ => return Ok(Some(ctx)),
+        };
+
+        let config: WebhookConfig = match get_config(&ctx, "webhook") {
+            Some(c) => c,
+            None => return Ok(Some(ctx)),
+        };
+
+        let targets: Vec<Endpoint> = config
+            .endpoints
+            .into_iter()
+            .filter(|ep| !ep.url.is_empty() && endpoint_matches(ep, ev))
+            .collect();
+
+        if targets.is_empty() {
+            return Ok(Some(ctx));
+        }
+
+        let body = simd_json::to_string(ev)?;
+        let (group_id, user_id) = match ctx.as_message() {
+            Some(m) => (m.group_id(), Some(m.user_id())),
+            None => (None, None),
+        };
+
+        // 后台投递，不阻塞插件链
+        for ep in targets {
+            let ctx = ctx.clone();
+            let writer = writer.clone();
+            let body = body.clone();
+            tokio::spawn(async move {
+                match deliver(&ep, &body).await {
+                    Ok(resp_body) => {
+                        if let Some(reply) = parse_reply(&resp_body)
+                            && (group_id.is_some() || user_id.is_some())
+                            && let Err(e) = send_msg(&ctx, writer, group_id, user_id, reply).await
+                        {
+                            warn!(target: "Plugin/Webhook", "回传消息发送失败: {}", e);
+                        }
+                    }
+                    Err(e) => {
+                        warn!(target: "Plugin/Webhook", "推送至 {} 失败，已写入死信: {}", ep.url, e);
+                        write_dead_letter(&ep.url, &body, &e).await;
+                    }
+                }
+            });
+        }
+
+        Ok(Some(ctx))
+    })
+}
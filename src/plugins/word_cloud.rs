@@ -1,7 +1,7 @@
 use crate::adapters::onebot::{LockedWriter, send_msg};
 use crate::command::get_prefixes;
 use crate::db::queries::get_text_corpus;
-use crate::db::utils::get_time_range;
+use crate::db::utils::{parse_time_range, time_expr_pattern};
 use crate::event::Context;
 use crate::message::Message;
 use crate::plugins::{PluginError, get_config};
@@ -14,16 +14,13 @@ pub mod image;
 pub mod stopwords;
 
 use config::WordCloudConfig;
-pub use config::default_config;
+pub use config::{default_config, validate_config};
 
 static COMMAND_REGEX: OnceLock<Regex> = OnceLock::new();
 
 fn get_regex() -> &'static Regex {
     COMMAND_REGEX.get_or_init(|| {
-        Regex::new(
-            r"^(本群|跨群|我的)(今日|昨日|本周|上周|近7天|近30天|本月|上月|今年|去年|总)词云$",
-        )
-        .unwrap()
+        Regex::new(&format!(r"^(本群|跨群|我的)({})词云$", time_expr_pattern())).unwrap()
     })
 }
 
@@ -65,7 +62,11 @@ pub fn handle(
 
             info!(target: "Plugin/WordCloud", "收到词云请求: Scope={}, Time={}", scope_str, time_str);
 
-            let (start_time, end_time) = get_time_range(time_str);
+            let Some((start_time, end_time)) = parse_time_range(time_str) else {
+                let reply = Message::new().text(format!("无法识别的时间: {}", time_str));
+                send_msg(&ctx, writer, msg.group_id(), Some(msg.user_id()), reply).await?;
+                return Ok(None);
+            };
 
             let (query_group_id, query_user_id) = match scope_str {
                 "本群" => {
@@ -1,4 +1,5 @@
 use crate::config::build_config;
+use crate::plugins::validate_as;
 use serde::{Deserialize, Serialize};
 use toml::Value;
 
@@ -46,3 +47,8 @@ pub fn default_config() -> Value {
         max_msg: 50000,
     })
 }
+
+/// 以实际的配置类型反序列化配置表，用于启动与重载时的校验
+pub fn validate_config(value: &Value) -> Result<(), (String, String)> {
+    validate_as::<WordCloudConfig>(value)
+}
@@ -1,4 +1,4 @@
-use super::stopwords::get_stop_words;
+use super::stopwords::is_meaningful;
 use araea_wordcloud::{WordCloudBuilder, WordInput};
 use base64::{Engine as _, engine::general_purpose};
 use image::{GenericImageView, ImageFormat};
@@ -28,19 +28,13 @@ pub fn generate_word_cloud(
 ) -> Result<String, String> {
     let start = Instant::now();
 
-    let stop_words = get_stop_words();
     let mut freq_map: HashMap<String, f64> = HashMap::new();
 
     for line in corpus {
         let words = line.split_whitespace();
         for w in words {
             let w_trim = w.trim();
-            if w_trim.chars().count() > 1
-                && !stop_words.contains(w_trim)
-                && !w_trim
-                    .chars()
-                    .all(|c| c.is_numeric() || c.is_ascii_punctuation())
-            {
+            if is_meaningful(w_trim) {
                 *freq_map.entry(w_trim.to_string()).or_insert(0.0) += 1.0;
             }
         }
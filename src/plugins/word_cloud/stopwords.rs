@@ -330,3 +330,12 @@ pub fn get_stop_words() -> &'static HashSet<&'static str> {
         list.into_iter().collect()
     })
 }
+
+/// 判断分词结果是否计入词频：长度大于 1、不是停用词、不是纯数字或标点
+pub fn is_meaningful(word: &str) -> bool {
+    word.chars().count() > 1
+        && !get_stop_words().contains(word)
+        && !word
+            .chars()
+            .all(|c| c.is_numeric() || c.is_ascii_punctuation())
+}
@@ -2,6 +2,7 @@
 
 use crate::adapters::onebot::{LockedWriter, api};
 use crate::event::Context;
+use crate::metrics;
 use chrono::{DateTime, Local, TimeZone};
 use std::collections::HashMap;
 use std::future::Future;
@@ -50,6 +51,7 @@ impl Scheduler {
 
                 // 执行任务
                 task_gen().await;
+                metrics::inc_counter(metrics::SCHEDULER_RUNS, &[]);
 
                 // 计算下一次
                 next_time = next_run_calculator(Local::now());